@@ -1,19 +1,16 @@
 //! Various types of cells in picross.
 
 use picore::Cell;
+pub use picore::SimpleCell;
 
-/// Binary cell. Either full or empty.
-#[derive(Debug, PartialEq, Copy, Clone)]
-pub struct SimpleCell;
-
-impl SimpleCell {
-    /// Gets the character representation of the given simple cell.
-    pub fn char_repr(cell: &Cell<SimpleCell>) -> char {
-        match *cell {
-            Cell::Empty => '.',
-            Cell::CrossedOut => '/',
-            Cell::Filled(_) => '#',
-        }
+/// Gets the character representation of the given simple cell.
+pub fn char_repr(cell: &Cell<SimpleCell>) -> char {
+    match *cell {
+        Cell::Empty => '.',
+        Cell::CrossedOut => '/',
+        Cell::Filled(_) => '#',
+        Cell::Marked => '?',
+        Cell::Tentative(_) => '+',
     }
 }
 
@@ -5,10 +5,13 @@
 use crate::cell::SimpleCell;
 use itertools::Itertools;
 use pancurses::{
-    curs_set, endwin, init_pair, initscr, noecho, start_color, Input, COLOR_BLACK, COLOR_GREEN, COLOR_PAIR, COLOR_WHITE,
+    curs_set, endwin, init_pair, initscr, noecho, start_color, Input, Window, COLOR_BLACK, COLOR_GREEN, COLOR_PAIR,
+    COLOR_WHITE, COLOR_YELLOW,
 };
-use picore::{constraints, Cell, Picross, Puzzle};
+use picore::{constraints, Cell, Collection, Metadata, Picross, Puzzle};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io, process};
 
 pub mod cell;
 
@@ -34,14 +37,74 @@ fn demo_puzzle() -> Puzzle<SimpleCell> {
             [1, SimpleCell]
         },
     )
+    .with_metadata(Metadata {
+        title: Some("Demo".to_owned()),
+        reveal: Some("Smiley".to_owned()),
+        ..Metadata::default()
+    })
 }
 
 const COLOR_SOLVED: i16 = 1;
 const COLOR_SELECTION: i16 = 2;
 const COLOR_SELECTION_SOLVED: i16 = 3;
+const COLOR_BRANCH: i16 = 4;
+
+/// Loads the collection at `path`, which is either a directory of `.non` files or a pack file.
+fn load_collection(path: &Path) -> io::Result<Collection<SimpleCell>> {
+    if path.is_dir() {
+        Collection::load_dir(path)
+    } else {
+        Collection::load_pack(path)
+    }
+}
+
+/// Returns where the player's progress on the collection at `path` is saved.
+fn progress_path(path: &Path) -> PathBuf {
+    path.with_extension("progress")
+}
+
+/// Reads the player's progress on `collection` from `path`, if it has been saved before.
+fn load_progress(collection: &mut Collection<SimpleCell>, path: &Path) -> io::Result<()> {
+    match fs::read_to_string(path) {
+        Ok(text) => collection
+            .read_progress(&text)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(error),
+    }
+}
+
+/// What the player chose to do after leaving a puzzle.
+enum Leave {
+    Quit,
+    Previous,
+    Next,
+}
 
 fn main() {
-    let mut picross = Picross::new(demo_puzzle());
+    let path = env::args_os().nth(1).map(PathBuf::from);
+    let mut collection = match &path {
+        Some(path) => load_collection(path).unwrap_or_else(|error| {
+            eprintln!("could not load {}: {}", path.display(), error);
+            process::exit(1);
+        }),
+        None => {
+            let mut collection = Collection::new(Metadata::default());
+            collection.push(demo_puzzle());
+            collection
+        }
+    };
+    if collection.is_empty() {
+        eprintln!("the collection has no puzzles");
+        process::exit(1);
+    }
+    let progress_path = path.as_deref().map(progress_path);
+    if let Some(progress_path) = &progress_path {
+        load_progress(&mut collection, progress_path).unwrap_or_else(|error| {
+            eprintln!("could not load {}: {}", progress_path.display(), error);
+            process::exit(1);
+        });
+    }
 
     let window = initscr();
     curs_set(0);
@@ -51,9 +114,50 @@ fn main() {
     init_pair(COLOR_SOLVED, COLOR_GREEN, COLOR_BLACK);
     init_pair(COLOR_SELECTION, COLOR_BLACK, COLOR_WHITE);
     init_pair(COLOR_SELECTION_SOLVED, COLOR_BLACK, COLOR_GREEN);
+    init_pair(COLOR_BRANCH, COLOR_YELLOW, COLOR_BLACK);
 
     window.keypad(true);
 
+    let mut index = collection.next_unsolved().unwrap_or(0);
+    loop {
+        let entry = collection.get(index).unwrap();
+        let mut picross = match &entry.progress.saved {
+            Some(board) => Picross::with_board(entry.puzzle.clone(), board.clone(), entry.progress.elapsed),
+            None => Picross::new(entry.puzzle.clone()),
+        };
+
+        let leave = play(&window, &mut picross, &collection, index);
+        match picross.report() {
+            Some(report) => {
+                collection.record_solve(index, report.stats.elapsed);
+            }
+            None => collection.save_game(index, picross.board().clone(), picross.elapsed()),
+        }
+
+        match leave {
+            Leave::Quit => break,
+            Leave::Previous => index = (index + collection.len() - 1) % collection.len(),
+            Leave::Next => index = (index + 1) % collection.len(),
+        }
+    }
+
+    endwin();
+
+    if let Some(progress_path) = &progress_path {
+        if let Err(error) = fs::write(progress_path, collection.write_progress()) {
+            eprintln!("could not save {}: {}", progress_path.display(), error);
+            process::exit(1);
+        }
+    }
+}
+
+/// Plays `picross`, the puzzle at `index` in `collection`, until the player leaves it.
+fn play(
+    window: &Window,
+    picross: &mut Picross<SimpleCell>,
+    collection: &Collection<SimpleCell>,
+    index: usize,
+) -> Leave {
     let mut row_sizes = vec![0; picross.row_constraints().iter().map(|c| c.len()).max().unwrap_or(0)];
     let mut col_sizes = vec![0; picross.column_constraints().len()];
 
@@ -84,19 +188,29 @@ fn main() {
 
     for r in 0..picross.height() {
         let mut col_offset = 0;
-        for c in 0..picross.width() {
+        for (c, col_size) in col_sizes.iter().enumerate() {
             board_pos.insert((r, c), (r as i32, col_offset as i32));
-            col_offset += col_sizes[c] + 1;
+            col_offset += col_size + 1;
         }
     }
 
     let mut pos = (0, 0);
 
-    let mut solved = false;
+    let mut solved = picross.is_solved();
 
     loop {
         window.clear();
-        window.mvprintw(1, window.get_max_x() - 20, format!("{:?}", pos));
+        let metadata = picross.metadata();
+        let title = if solved { metadata.reveal.as_ref() } else { None }.or(metadata.title.as_ref());
+        if let Some(title) = title {
+            window.mvprintw(0, window.get_max_x() - 23, title);
+        }
+        window.mvprintw(
+            1,
+            window.get_max_x() - 23,
+            format!("{}/{} ({} solved)", index + 1, collection.len(), collection.completed()),
+        );
+        window.mvprintw(6, window.get_max_x() - 23, format!("{:?}", pos));
         {
             let (row_status, column_status) = picross.status();
             window.mvprintw(
@@ -112,6 +226,14 @@ fn main() {
                     column_status.iter().map(|s| if *s { "1" } else { "0" }).join("")
                 ),
             );
+            window.mvprintw(
+                4,
+                window.get_max_x() - 23,
+                format!("t: {}s", picross.elapsed().as_secs()),
+            );
+            if let Some(report) = picross.report() {
+                window.mvprintw(5, window.get_max_x() - 23, format!("score: {}", report.score));
+            }
         }
 
         for (i, constraint) in picross.row_constraints().iter().enumerate() {
@@ -139,8 +261,12 @@ fn main() {
         }
 
         for (r, c, cell) in picross.cells() {
+            let in_branch = picross.branch_at(r, c).is_some();
+
             if solved {
                 window.attron(COLOR_PAIR(COLOR_SOLVED as _));
+            } else if in_branch {
+                window.attron(COLOR_PAIR(COLOR_BRANCH as _));
             }
 
             if r == pos.0 && c == pos.1 {
@@ -154,11 +280,13 @@ fn main() {
             window.mvaddch(
                 board_base.0 + board_pos[&(r, c)].0,
                 board_base.1 + board_pos[&(r, c)].1,
-                SimpleCell::char_repr(cell),
+                cell::char_repr(cell),
             );
 
             if solved {
                 window.attroff(COLOR_PAIR(COLOR_SOLVED as _));
+            } else if in_branch {
+                window.attroff(COLOR_PAIR(COLOR_BRANCH as _));
             }
 
             if r == pos.0 && c == pos.1 {
@@ -171,26 +299,71 @@ fn main() {
         }
 
         match window.getch() {
-            Some(Input::KeyDC) => break,
+            Some(Input::KeyDC) => return Leave::Quit,
+            Some(Input::KeyPPage) => return Leave::Previous,
+            Some(Input::KeyNPage) => return Leave::Next,
             Some(Input::KeyLeft) => pos.1 = (pos.1 + picross.width() - 1) % picross.width(),
             Some(Input::KeyRight) => pos.1 = (pos.1 + picross.width() + 1) % picross.width(),
             Some(Input::KeyUp) => pos.0 = (pos.0 + picross.height() - 1) % picross.height(),
             Some(Input::KeyDown) => pos.0 = (pos.0 + picross.height() + 1) % picross.height(),
             Some(Input::Character('c')) => {
                 solved = match picross.get(pos.0, pos.1) {
-                    Cell::Empty | Cell::Filled(_) => picross.cross_out(pos.0, pos.1),
+                    Cell::Empty | Cell::Filled(_) | Cell::Marked | Cell::Tentative(_) => {
+                        picross.cross_out(pos.0, pos.1)
+                    }
                     Cell::CrossedOut => picross.clear_at(pos.0, pos.1),
                 }
+                .unwrap_or(solved)
             }
             Some(Input::Character(' ')) => {
                 solved = match picross.get(pos.0, pos.1) {
-                    Cell::Empty | Cell::CrossedOut => picross.place_at(SimpleCell, pos.0, pos.1),
+                    Cell::Empty | Cell::CrossedOut | Cell::Marked | Cell::Tentative(_) => {
+                        picross.place_at(SimpleCell, pos.0, pos.1)
+                    }
                     Cell::Filled(_) => picross.clear_at(pos.0, pos.1),
                 }
+                .unwrap_or(solved)
+            }
+            Some(Input::Character('m')) => {
+                solved = match picross.get(pos.0, pos.1) {
+                    Cell::Marked => picross.clear_at(pos.0, pos.1),
+                    _ => picross.mark_at(pos.0, pos.1),
+                }
+                .unwrap_or(solved)
+            }
+            Some(Input::Character('t')) => {
+                solved = match picross.get(pos.0, pos.1) {
+                    Cell::Tentative(_) => picross.clear_at(pos.0, pos.1),
+                    _ => picross.place_tentative(SimpleCell, pos.0, pos.1),
+                }
+                .unwrap_or(solved)
+            }
+            Some(Input::Character('y')) => solved = picross.commit_tentative(),
+            Some(Input::Character('n')) => solved = picross.discard_tentative(),
+            Some(Input::Character('g')) => {
+                let name = format!("guess {}", picross.checkpoints().count() + 1);
+                picross.checkpoint(name);
+            }
+            Some(Input::Character('k')) => {
+                if let Some(name) = picross.checkpoints().last().map(str::to_owned) {
+                    picross.commit_checkpoint(&name);
+                }
+            }
+            Some(Input::Character('z')) => {
+                if let Some(name) = picross.checkpoints().last().map(str::to_owned) {
+                    picross.rollback(&name);
+                    solved = picross.is_solved();
+                }
+            }
+            Some(Input::Character('u')) => {
+                picross.undo();
+                solved = picross.is_solved();
+            }
+            Some(Input::Character('r')) => {
+                picross.redo();
+                solved = picross.is_solved();
             }
             _ => {}
         };
     }
-
-    endwin();
 }
@@ -0,0 +1,113 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use picore::{Board, Cell, Constraint, ConstraintEntry, Picross, Puzzle};
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+struct Black;
+
+fn is_filled(row: usize, column: usize) -> bool {
+    (row * 7 + column * 3) % 5 < 3
+}
+
+fn constraint(cells: impl Iterator<Item = bool>) -> Constraint<Black> {
+    let mut constraint: Constraint<Black> = Vec::new();
+    let mut previous = false;
+    for filled in cells {
+        match (previous, filled) {
+            (false, true) => constraint.push(ConstraintEntry { value: Black, size: 1 }),
+            (true, true) => constraint.last_mut().unwrap().size += 1,
+            _ => {}
+        }
+        previous = filled;
+    }
+    constraint
+}
+
+fn puzzle(size: usize) -> Puzzle<Black> {
+    Puzzle::new(
+        (0..size)
+            .map(|r| constraint((0..size).map(|c| is_filled(r, c))))
+            .collect(),
+        (0..size)
+            .map(|c| constraint((0..size).map(|r| is_filled(r, c))))
+            .collect(),
+    )
+}
+
+fn solved_board(size: usize) -> Board<Black> {
+    let mut board = Board::new_empty(size, size);
+    for r in 0..size {
+        for c in 0..size {
+            *board.get_mut(r, c) = if is_filled(r, c) {
+                Cell::Filled(Black)
+            } else {
+                Cell::CrossedOut
+            };
+        }
+    }
+    board
+}
+
+/// What a move used to cost: rescanning the edited row and column, and rescanning
+/// every line crossing them at a crossed out cell to auto-cross.
+fn full_scan(puzzle: &Puzzle<Black>, board: &Board<Black>, row: usize, column: usize) {
+    rescan(puzzle, board, row, column);
+    for (c, cell) in board.row(row).iter().enumerate() {
+        if let Cell::CrossedOut = cell {
+            black_box(puzzle.column_is_solved(board, c));
+        }
+    }
+    for (r, cell) in board.column(column).enumerate() {
+        if let Cell::CrossedOut = cell {
+            black_box(puzzle.row_is_solved(board, r));
+        }
+    }
+}
+
+/// What an undo used to cost: rescanning the edited row and column.
+fn rescan(puzzle: &Puzzle<Black>, board: &Board<Black>, row: usize, column: usize) {
+    black_box(puzzle.row_is_solved(board, row));
+    black_box(puzzle.column_is_solved(board, column));
+}
+
+fn edit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("edit");
+
+    for &size in &[25, 50, 100, 200] {
+        // The first filled cell on the diagonal.
+        let middle = (size / 2..size).find(|&i| is_filled(i, i)).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("incremental", size), &size, |b, &size| {
+            let mut picross = Picross::new(puzzle(size));
+            picross.paint_rect(Cell::CrossedOut, (0, 0), (size - 1, size - 1));
+            for (r, c, cell) in solved_board(size).cells() {
+                if let Cell::Filled(value) = *cell {
+                    picross.place_at(value, r, c).unwrap();
+                }
+            }
+            assert!(picross.is_solved());
+
+            b.iter(|| {
+                black_box(picross.clear_at(middle, middle).unwrap());
+                picross.undo();
+            });
+        });
+
+        // The same moves: clearing the cell, then undoing that.
+        group.bench_with_input(BenchmarkId::new("full_scan", size), &size, |b, &size| {
+            let puzzle = puzzle(size);
+            let mut board = solved_board(size);
+
+            b.iter(|| {
+                let before = std::mem::replace(board.get_mut(middle, middle), Cell::Empty);
+                full_scan(&puzzle, &board, middle, middle);
+                *board.get_mut(middle, middle) = before;
+                rescan(&puzzle, &board, middle, middle);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, edit);
+criterion_main!(benches);
@@ -0,0 +1,136 @@
+#ifndef PICORE_H
+#define PICORE_H
+
+/* Generated from src/ffi.rs by cbindgen. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// What a cell of a game contains.
+typedef enum PicoreCell {
+  // An empty cell.
+  PICORE_CELL_EMPTY,
+  // A crossed out cell.
+  PICORE_CELL_CROSSED_OUT,
+  // A filled cell.
+  PICORE_CELL_FILLED,
+  // An empty cell the player has marked as possibly filled.
+  PICORE_CELL_MARKED,
+  // A cell the player has tentatively filled, while testing a guess.
+  PICORE_CELL_TENTATIVE,
+} PicoreCell;
+
+// The result of a move.
+typedef enum PicoreMove {
+  // The move was made, and the puzzle isn't solved.
+  PICORE_MOVE_UNSOLVED,
+  // The move was made, and the puzzle is solved.
+  PICORE_MOVE_SOLVED,
+  // The cell is locked, so nothing changed.
+  PICORE_MOVE_LOCKED,
+  // There is no cell there, so nothing changed.
+  PICORE_MOVE_OUT_OF_BOUNDS,
+} PicoreMove;
+
+// A game of a black and white puzzle.
+typedef struct PicoreGame PicoreGame;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Creates a game from the NUL-terminated `.non` text `text`. Returns NULL if `text` is NULL,
+// isn't UTF-8 or isn't a valid puzzle.
+//
+// # Safety
+// `text` must be NULL or point to a NUL-terminated string.
+struct PicoreGame *picore_game_from_non(const char *text);
+
+// Frees `game`. Does nothing if it is NULL.
+//
+// # Safety
+// `game` must be NULL or a game from [`picore_game_from_non`] which hasn't been freed.
+void picore_game_free(struct PicoreGame *game);
+
+// The number of columns in `game`.
+//
+// # Safety
+// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+size_t picore_game_width(const struct PicoreGame *game);
+
+// The number of rows in `game`.
+//
+// # Safety
+// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+size_t picore_game_height(const struct PicoreGame *game);
+
+// Fills the cell at `row` and `column`.
+//
+// # Safety
+// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+enum PicoreMove picore_game_place(struct PicoreGame *game, size_t row, size_t column);
+
+// Crosses out the cell at `row` and `column`.
+//
+// # Safety
+// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+enum PicoreMove picore_game_cross_out(struct PicoreGame *game, size_t row, size_t column);
+
+// Empties the cell at `row` and `column`.
+//
+// # Safety
+// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+enum PicoreMove picore_game_clear(struct PicoreGame *game, size_t row, size_t column);
+
+// Returns the cell at `row` and `column`, or [`PicoreCell::Empty`] if there is none.
+//
+// # Safety
+// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+enum PicoreCell picore_game_cell(const struct PicoreGame *game, size_t row, size_t column);
+
+// Writes the block sizes of line `index` in `family` to `sizes`, which has room for `len` of
+// them. Returns the number of blocks, which may be more than `len`, or 0 if there is no such
+// line.
+//
+// # Safety
+// `game` must be a game from [`picore_game_from_non`] which hasn't been freed, and `sizes` must
+// point to `len` writable values, or may be NULL if `len` is 0.
+size_t picore_game_clue(const struct PicoreGame *game,
+                        size_t family,
+                        size_t index,
+                        size_t *sizes,
+                        size_t len);
+
+// Whether or not line `index` in `family` satisfies its clue. Returns false if there is no
+// such line.
+//
+// # Safety
+// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+bool picore_game_is_line_solved(const struct PicoreGame *game, size_t family, size_t index);
+
+// Whether or not `game` is solved.
+//
+// # Safety
+// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+bool picore_game_is_solved(const struct PicoreGame *game);
+
+// Undoes the last move. Returns whether or not there was a move to undo.
+//
+// # Safety
+// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+bool picore_game_undo(struct PicoreGame *game);
+
+// Redoes the last undone move. Returns whether or not there was a move to redo.
+//
+// # Safety
+// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+bool picore_game_redo(struct PicoreGame *game);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* PICORE_H */
@@ -0,0 +1,189 @@
+use crate::{Board, Cell, Grid, SimpleCell};
+use bitvec::prelude::*;
+
+/// A bit-packed board for black and white puzzles.
+///
+/// Stores a bitplane for each kind of cell, all in row-major order, so whole rows
+/// can be read as bit slices. At most one plane is set for any cell.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BitBoard {
+    filled: BitVec,
+    crossed: BitVec,
+    marked: BitVec,
+    tentative: BitVec,
+    width: usize,
+    height: usize,
+}
+
+impl BitBoard {
+    /// Creates a new board with `width` columns and `height` rows.
+    pub fn new_empty(width: usize, height: usize) -> Self {
+        Self {
+            filled: bitvec![0; width * height],
+            crossed: bitvec![0; width * height],
+            marked: bitvec![0; width * height],
+            tentative: bitvec![0; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// The width of this board.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height of this board.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the cell at `row` and `col`.
+    pub fn get(&self, row: usize, col: usize) -> Cell<SimpleCell> {
+        let index = self.index(row, col);
+        if self.filled[index] {
+            Cell::Filled(SimpleCell)
+        } else if self.crossed[index] {
+            Cell::CrossedOut
+        } else if self.marked[index] {
+            Cell::Marked
+        } else if self.tentative[index] {
+            Cell::Tentative(SimpleCell)
+        } else {
+            Cell::Empty
+        }
+    }
+
+    /// Sets the cell at `row` and `col`.
+    pub fn set(&mut self, row: usize, col: usize, cell: Cell<SimpleCell>) {
+        let index = self.index(row, col);
+        self.filled.set(index, matches!(cell, Cell::Filled(_)));
+        self.crossed.set(index, matches!(cell, Cell::CrossedOut));
+        self.marked.set(index, matches!(cell, Cell::Marked));
+        self.tentative.set(index, matches!(cell, Cell::Tentative(_)));
+    }
+
+    /// Returns the filled bits of the row at `index`.
+    pub fn filled_row(&self, index: usize) -> &BitSlice {
+        let start = index * self.width;
+        &self.filled[start..(start + self.width)]
+    }
+
+    /// Returns the crossed out bits of the row at `index`.
+    pub fn crossed_row(&self, index: usize) -> &BitSlice {
+        let start = index * self.width;
+        &self.crossed[start..(start + self.width)]
+    }
+
+    /// The number of filled cells in this board.
+    pub fn count_filled(&self) -> usize {
+        self.filled.count_ones()
+    }
+
+    fn index(&self, row: usize, col: usize) -> usize {
+        assert!(col < self.width, "column {} out of bounds", col);
+        (row * self.width) + col
+    }
+}
+
+impl Grid<SimpleCell> for BitBoard {
+    fn new_empty(width: usize, height: usize) -> Self {
+        BitBoard::new_empty(width, height)
+    }
+
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn cell(&self, row: usize, col: usize) -> Cell<SimpleCell> {
+        self.get(row, col)
+    }
+
+    fn set_cell(&mut self, row: usize, col: usize, cell: Cell<SimpleCell>) {
+        self.set(row, col, cell)
+    }
+}
+
+impl From<&Board<SimpleCell>> for BitBoard {
+    fn from(board: &Board<SimpleCell>) -> Self {
+        let mut bits = BitBoard::new_empty(board.width(), board.height());
+        for (r, c, cell) in board.cells() {
+            bits.set(r, c, *cell);
+        }
+        bits
+    }
+}
+
+impl From<&BitBoard> for Board<SimpleCell> {
+    fn from(bits: &BitBoard) -> Self {
+        let mut board = Board::new_empty(bits.width(), bits.height());
+        for r in 0..bits.height() {
+            for c in 0..bits.width() {
+                *board.get_mut(r, c) = bits.get(r, c);
+            }
+        }
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{constraints, BitBoard, Board, Cell, Puzzle, SimpleCell};
+
+    fn test_board() -> Board<SimpleCell> {
+        use crate::Cell::*;
+        let filled = Filled(SimpleCell);
+
+        #[rustfmt::skip]
+        return Board::new_raw(vec![
+            Empty,      filled, filled,     CrossedOut, Empty,
+            filled,     Empty,  CrossedOut, filled,     Empty,
+            CrossedOut, filled, filled,     Marked,     Tentative(SimpleCell),
+        ], 5, 3);
+    }
+
+    #[test]
+    fn round_trips() {
+        let board = test_board();
+        let bits = BitBoard::from(&board);
+
+        assert_eq!(bits.get(0, 1), Cell::Filled(SimpleCell));
+        assert_eq!(bits.get(1, 2), Cell::CrossedOut);
+        assert_eq!(bits.get(0, 4), Cell::Empty);
+        assert_eq!(bits.get(2, 3), Cell::Marked);
+        assert_eq!(bits.get(2, 4), Cell::Tentative(SimpleCell));
+        assert_eq!(bits.count_filled(), 6);
+        assert_eq!(
+            bits.filled_row(1).iter().by_val().collect::<Vec<_>>(),
+            [true, false, false, true, false]
+        );
+        assert_eq!(Board::from(&bits), board);
+    }
+
+    #[test]
+    fn check_works() {
+        let puzzle = Puzzle::new(
+            constraints![
+                [2, SimpleCell]
+                [1, SimpleCell; 1, SimpleCell]
+                [2, SimpleCell]
+            ],
+            constraints![
+                [1, SimpleCell]
+                [1, SimpleCell; 1, SimpleCell]
+                [1, SimpleCell; 1, SimpleCell]
+                [1, SimpleCell]
+                []
+            ],
+        );
+        let mut bits = BitBoard::from(&test_board());
+        assert!(puzzle.is_solved_by(&bits));
+
+        bits.set(2, 4, Cell::Filled(SimpleCell));
+        assert!(!puzzle.is_solved_by(&bits));
+    }
+}
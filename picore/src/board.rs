@@ -1,30 +1,57 @@
 use crate::cell::CellValue;
-use crate::Cell;
+use crate::{Cell, Geometry, LineId, Transform};
+use alloc::vec;
+use alloc::vec::Vec;
+use bitvec::prelude::*;
 use itertools::Itertools;
 
 /// A Picross board.
+///
+/// Boards are equal if they have the same cells, whether or not the same cells are locked.
+#[derive(Clone, Debug)]
 pub struct Board<C: CellValue> {
     // Items are stored in row-major order.
     items: Vec<Cell<C>>,
+    // Also row-major.
+    locked: BitVec,
     width: usize,
     height: usize,
 }
 
+impl<C: CellValue> PartialEq for Board<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && self.height == other.height && self.items == other.items
+    }
+}
+
 impl<C: CellValue> Board<C> {
     /// Creates a new board with `width` columns and `height` rows.
     pub fn new_empty(width: usize, height: usize) -> Self {
         Self {
             items: vec![Cell::Empty; width * height],
+            locked: bitvec![0; width * height],
             width,
             height,
         }
     }
+
+    /// Creates a new empty board big enough for every cell in `geometry`.
+    pub fn for_geometry(geometry: &Geometry) -> Self {
+        let (width, height) = geometry.dimensions();
+        Self::new_empty(width, height)
+    }
 }
 
 impl<C: CellValue> Board<C> {
+    /// Creates a board directly from its row-major `items`.
     #[cfg(test)]
     pub fn new_raw(items: Vec<Cell<C>>, width: usize, height: usize) -> Self {
-        Self { items, width, height }
+        Self {
+            locked: bitvec![0; items.len()],
+            items,
+            width,
+            height,
+        }
     }
 
     /// The width of this board.
@@ -48,6 +75,14 @@ impl<C: CellValue> Board<C> {
         Column { puzzle: self, index }
     }
 
+    /// Returns an iterator over the cells of `line` in `geometry`, in reading order.
+    pub fn line<'a>(&'a self, geometry: &Geometry, line: LineId) -> impl Iterator<Item = &'a Cell<C>> {
+        geometry
+            .line(line)
+            .into_iter()
+            .map(move |(row, col)| self.get(row, col))
+    }
+
     /// Returns an iterator over the rows of this board.
     pub fn rows(&self) -> impl Iterator<Item = &[Cell<C>]> {
         (0..self.height).map(move |row| self.row(row))
@@ -74,6 +109,29 @@ impl<C: CellValue> Board<C> {
     pub fn get_mut(&mut self, row: usize, col: usize) -> &mut Cell<C> {
         &mut self.items[(row * self.width) + col]
     }
+
+    /// Whether or not the cell at `row` and `col` is locked. Board methods ignore locks, but
+    /// [`Picross`](crate::Picross) doesn't let players change locked cells.
+    pub fn is_locked(&self, row: usize, col: usize) -> bool {
+        self.locked[(row * self.width) + col]
+    }
+
+    /// Locks or unlocks the cell at `row` and `col`.
+    pub fn set_locked(&mut self, row: usize, col: usize, locked: bool) {
+        self.locked.set((row * self.width) + col, locked);
+    }
+
+    /// Returns a copy of this board with `transform` applied.
+    pub fn transformed(&self, transform: Transform) -> Self {
+        let (width, height) = transform.dimensions(self.width, self.height);
+        let mut board = Self::new_empty(width, height);
+        for (r, c, cell) in self.cells() {
+            let (new_r, new_c) = transform.map(r, c, self.width, self.height);
+            *board.get_mut(new_r, new_c) = *cell;
+            board.set_locked(new_r, new_c, self.is_locked(r, c));
+        }
+        board
+    }
 }
 
 struct Column<'a, C: CellValue> {
@@ -109,6 +167,7 @@ mod tests {
                 filled, Empty,  Empty,  filled, Empty,
                 Empty,  filled, filled, Empty,  Empty,
             ],
+            locked: bitvec::bitvec![0; 15],
             width: 5,
             height: 3,
         }
@@ -174,4 +233,15 @@ mod tests {
         assert_eq!(*puzzle.get(2, 1), filled);
         assert_eq!(*puzzle.get(1, 4), Empty);
     }
+
+    #[test]
+    fn locks_dont_affect_equality() {
+        let board = test_board();
+        let mut locked = test_board();
+        locked.set_locked(0, 1, true);
+        assert_eq!(locked, board);
+
+        *locked.get_mut(0, 0) = crate::Cell::CrossedOut;
+        assert_ne!(locked, board);
+    }
 }
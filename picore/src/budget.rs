@@ -0,0 +1,141 @@
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+/// A flag which stops solving when set, possibly from another thread.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Creates a token which hasn't been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels everything using this token, or a clone of it.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether or not this token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Limits on how much work solving may do before giving up. Nothing is limited by default.
+#[derive(Clone, Debug, Default)]
+pub struct Budget {
+    timeout: Option<Duration>,
+    max_nodes: Option<usize>,
+    cancel: Option<CancelToken>,
+}
+
+impl Budget {
+    /// Creates a budget without limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gives up after `timeout` has passed since solving started.
+    #[cfg(feature = "std")]
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Gives up after searching `max_nodes` positions, each of which follows a guess.
+    pub fn with_max_nodes(self, max_nodes: usize) -> Self {
+        Self {
+            max_nodes: Some(max_nodes),
+            ..self
+        }
+    }
+
+    /// Gives up once `cancel` is cancelled.
+    pub fn with_cancel(self, cancel: CancelToken) -> Self {
+        Self {
+            cancel: Some(cancel),
+            ..self
+        }
+    }
+
+    /// Returns what is left of this budget after `elapsed` has been spent.
+    pub(crate) fn after(&self, elapsed: Duration) -> Self {
+        Self {
+            timeout: self.timeout.map(|timeout| timeout.saturating_sub(elapsed)),
+            ..self.clone()
+        }
+    }
+
+    /// Starts spending this budget.
+    pub(crate) fn start(&self) -> Limits<'_> {
+        Limits {
+            budget: self,
+            #[cfg(feature = "std")]
+            deadline: self.timeout.map(|timeout| Instant::now() + timeout),
+        }
+    }
+}
+
+/// A budget being spent by one call.
+pub(crate) struct Limits<'a> {
+    budget: &'a Budget,
+    #[cfg(feature = "std")]
+    deadline: Option<Instant>,
+}
+
+impl Limits<'_> {
+    /// Whether or not solving was cancelled or ran out of time.
+    pub(crate) fn is_interrupted(&self) -> bool {
+        self.budget.cancel.as_ref().is_some_and(CancelToken::is_cancelled) || self.is_overdue()
+    }
+
+    #[cfg(feature = "std")]
+    fn is_overdue(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    // Without a clock, there are no timeouts.
+    #[cfg(not(feature = "std"))]
+    fn is_overdue(&self) -> bool {
+        false
+    }
+
+    /// Whether or not searching `nodes` positions is too many.
+    pub(crate) fn is_exceeded_by(&self, nodes: usize) -> bool {
+        self.budget.max_nodes.is_some_and(|max_nodes| nodes > max_nodes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Budget, CancelToken};
+    #[cfg(feature = "std")]
+    use std::time::Duration;
+
+    #[test]
+    fn limits_work() {
+        let cancel = CancelToken::new();
+        let budget = Budget::new().with_max_nodes(10).with_cancel(cancel.clone());
+        let limits = budget.start();
+
+        assert!(!limits.is_exceeded_by(10));
+        assert!(limits.is_exceeded_by(11));
+        assert!(!limits.is_interrupted());
+        cancel.cancel();
+        assert!(limits.is_interrupted());
+        assert!(!Budget::new().start().is_interrupted());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn timeouts_work() {
+        let budget = Budget::new().with_timeout(Duration::from_secs(0));
+        assert!(budget.start().is_interrupted());
+    }
+}
@@ -1,7 +1,7 @@
 /// Utility trait for types that can be cell values.
-pub trait CellValue: std::fmt::Debug + PartialEq + Copy + Clone {}
+pub trait CellValue: core::fmt::Debug + PartialEq + Copy + Clone {}
 
-impl<C: std::fmt::Debug + PartialEq + Copy + Clone> CellValue for C {}
+impl<C: core::fmt::Debug + PartialEq + Copy + Clone> CellValue for C {}
 
 /// A cell in a picross board.
 #[derive(PartialEq, Copy, Clone, Debug)]
@@ -12,13 +12,21 @@ pub enum Cell<C: CellValue> {
     CrossedOut,
     /// A cell with a value.
     Filled(C),
+    /// An empty cell the player has marked as possibly filled.
+    Marked,
+    /// A cell the player has tentatively filled with a value, while testing a guess.
+    Tentative(C),
 }
 
+/// Binary cell value. Either full or empty.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct SimpleCell;
+
 impl<C: CellValue> Cell<C> {
     /// Whether or not this cell is ignored by constraints.
     pub fn is_ignored(&self) -> bool {
         match self {
-            Cell::Empty | Cell::CrossedOut => true,
+            Cell::Empty | Cell::CrossedOut | Cell::Marked | Cell::Tentative(_) => true,
             Cell::Filled(_) => false,
         }
     }
@@ -0,0 +1,284 @@
+use crate::cell::CellValue;
+use crate::{Cell, Constraint, Geometry, LineId};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A maximal run of filled cells with the same value.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Run<C> {
+    start: usize,
+    len: usize,
+    value: C,
+}
+
+impl<C> Run<C> {
+    fn end(&self) -> usize {
+        self.start + self.len
+    }
+}
+
+/// The runs in a single line, kept up to date as its cells change.
+///
+/// A line of `len` cells holds at most `len` runs (adjacent runs may differ in
+/// value), so that much is reserved up front and updates never allocate.
+pub(crate) struct LineRuns<C> {
+    // Sorted by start, never overlapping.
+    runs: Vec<Run<C>>,
+}
+
+impl<C: CellValue> LineRuns<C> {
+    /// Creates the runs of an empty line with `len` cells.
+    pub fn new(len: usize) -> Self {
+        Self {
+            runs: Vec::with_capacity(len),
+        }
+    }
+
+    /// The index of the first run starting after `index`.
+    fn after(&self, index: usize) -> usize {
+        self.runs.partition_point(|run| run.start <= index)
+    }
+
+    /// Updates the runs after the cell at `index` changed from `before` to `after`.
+    pub fn update(&mut self, index: usize, before: &Cell<C>, after: &Cell<C>) {
+        let before = match *before {
+            Cell::Filled(value) => Some(value),
+            _ => None,
+        };
+        let after = match *after {
+            Cell::Filled(value) => Some(value),
+            _ => None,
+        };
+
+        if before == after {
+            return;
+        }
+        if before.is_some() {
+            self.remove(index);
+        }
+        if let Some(value) = after {
+            self.insert(index, value);
+        }
+    }
+
+    /// Removes the filled cell at `index` from the run containing it.
+    fn remove(&mut self, index: usize) {
+        let i = self.after(index) - 1;
+        let run = self.runs[i];
+        debug_assert!(run.start <= index && index < run.end());
+
+        if run.len == 1 {
+            self.runs.remove(i);
+        } else if index == run.start {
+            self.runs[i].start += 1;
+            self.runs[i].len -= 1;
+        } else if index == run.end() - 1 {
+            self.runs[i].len -= 1;
+        } else {
+            self.runs[i].len = index - run.start;
+            self.runs.insert(
+                i + 1,
+                Run {
+                    start: index + 1,
+                    len: run.end() - index - 1,
+                    value: run.value,
+                },
+            );
+        }
+    }
+
+    /// Adds a cell filled with `value` at `index`, which must not be in a run.
+    fn insert(&mut self, index: usize, value: C) {
+        let i = self.after(index);
+        let joins_left = i > 0 && self.runs[i - 1].end() == index && self.runs[i - 1].value == value;
+        let joins_right = i < self.runs.len() && self.runs[i].start == index + 1 && self.runs[i].value == value;
+
+        match (joins_left, joins_right) {
+            (true, true) => {
+                let right = self.runs.remove(i);
+                self.runs[i - 1].len += 1 + right.len;
+            }
+            (true, false) => self.runs[i - 1].len += 1,
+            (false, true) => {
+                self.runs[i].start -= 1;
+                self.runs[i].len += 1;
+            }
+            (false, false) => self.runs.insert(
+                i,
+                Run {
+                    start: index,
+                    len: 1,
+                    value,
+                },
+            ),
+        }
+    }
+
+    /// Whether or not the runs are exactly those described by `constraint`.
+    pub fn satisfies(&self, constraint: &Constraint<C>) -> bool {
+        self.runs.len() == constraint.len()
+            && self
+                .runs
+                .iter()
+                .zip(constraint)
+                .all(|(run, entry)| run.len == entry.size && run.value == entry.value)
+    }
+}
+
+/// The most lines through a single cell, in the triangle and hexagon geometries.
+pub(crate) const MAX_CROSSINGS: usize = 3;
+
+/// A short list of lines, copied out of the checker so it can be iterated while the game changes.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Lines<const N: usize> {
+    lines: [LineId; N],
+    len: usize,
+    next: usize,
+}
+
+impl<const N: usize> Lines<N> {
+    /// Creates an empty list.
+    pub fn new() -> Self {
+        Self {
+            lines: [LineId::new(0, 0); N],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    /// Adds `line` to the end of the list.
+    ///
+    /// # Panics
+    /// Panics if the list already holds `N` lines.
+    pub fn push(&mut self, line: LineId) {
+        self.lines[self.len] = line;
+        self.len += 1;
+    }
+}
+
+impl<const N: usize> Iterator for Lines<N> {
+    type Item = LineId;
+
+    fn next(&mut self) -> Option<LineId> {
+        let line = self.lines[..self.len].get(self.next).copied()?;
+        self.next += 1;
+        Some(line)
+    }
+}
+
+/// Run summaries for every line of a board, and the cells of each line.
+///
+/// Keeping them up to date never allocates. Lines the runs can't judge on their own, such as
+/// wrapping lines, total and count clues and bands of mega clues, are rescanned with
+/// [`Puzzle::line_is_solved`](crate::Puzzle::line_is_solved) instead, which does.
+pub(crate) struct Checker<C> {
+    // Indexed by family, then by line.
+    lines: Vec<Vec<LineRuns<C>>>,
+    // The positions of each line's cells, in order, indexed like `lines`.
+    cells: Vec<Vec<Vec<(usize, usize)>>>,
+    // For each board position, the lines through it and its index in each.
+    crossings: Vec<Vec<(LineId, usize)>>,
+    width: usize,
+}
+
+impl<C: CellValue> Checker<C> {
+    /// Creates the summaries of an empty board shaped like `geometry`.
+    pub fn new(geometry: &Geometry) -> Self {
+        let (width, height) = geometry.dimensions();
+        let mut crossings = vec![Vec::new(); width * height];
+        let cells = (0..geometry.families())
+            .map(|family| {
+                (0..geometry.line_count(family))
+                    .map(|index| {
+                        let line = LineId::new(family, index);
+                        let cells = geometry.line(line);
+                        for (i, &(row, col)) in cells.iter().enumerate() {
+                            crossings[row * width + col].push((line, i));
+                        }
+                        cells
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let lines = cells
+            .iter()
+            .map(|family| family.iter().map(|cells| LineRuns::new(cells.len())).collect())
+            .collect();
+        Self {
+            lines,
+            cells,
+            crossings,
+            width,
+        }
+    }
+
+    /// Updates the summaries after the cell at `row` and `column` changed from `before` to `after`.
+    pub fn update(&mut self, row: usize, column: usize, before: &Cell<C>, after: &Cell<C>) {
+        for &(line, index) in &self.crossings[row * self.width + column] {
+            self.lines[line.family][line.index].update(index, before, after);
+        }
+    }
+
+    /// Returns the lines through the cell at `row` and `column`.
+    pub fn crossings(&self, row: usize, column: usize) -> Lines<MAX_CROSSINGS> {
+        let mut lines = Lines::new();
+        for &(line, _) in &self.crossings[row * self.width + column] {
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// Returns the positions of the cells in `line`, as `(row, column)` pairs.
+    pub fn cells(&self, line: LineId) -> &[(usize, usize)] {
+        &self.cells[line.family][line.index]
+    }
+
+    /// Returns the runs in `line`.
+    pub fn line(&self, line: LineId) -> &LineRuns<C> {
+        &self.lines[line.family][line.index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LineRuns;
+    use crate::{constraints, Cell};
+
+    #[derive(PartialEq, Copy, Clone, Debug)]
+    enum Color {
+        Red,
+        Blue,
+    }
+
+    fn set(runs: &mut LineRuns<Color>, line: &mut [Cell<Color>], index: usize, cell: Cell<Color>) {
+        runs.update(index, &line[index], &cell);
+        line[index] = cell;
+    }
+
+    #[test]
+    fn splits_and_merges() {
+        use Color::*;
+        let mut line = [Cell::Empty; 7];
+        let mut runs = LineRuns::new(line.len());
+
+        for i in 1..6 {
+            set(&mut runs, &mut line, i, Cell::Filled(Red));
+        }
+        assert!(runs.satisfies(&constraints![[5, Red]][0]));
+
+        set(&mut runs, &mut line, 3, Cell::CrossedOut);
+        assert!(runs.satisfies(&constraints![[2, Red; 2, Red]][0]));
+
+        set(&mut runs, &mut line, 3, Cell::Filled(Blue));
+        assert!(runs.satisfies(&constraints![[2, Red; 1, Blue; 2, Red]][0]));
+
+        set(&mut runs, &mut line, 3, Cell::Filled(Red));
+        assert!(runs.satisfies(&constraints![[5, Red]][0]));
+
+        set(&mut runs, &mut line, 1, Cell::Empty);
+        set(&mut runs, &mut line, 5, Cell::Empty);
+        set(&mut runs, &mut line, 6, Cell::Filled(Red));
+        assert!(runs.satisfies(&constraints![[3, Red; 1, Red]][0]));
+        assert_eq!(runs.runs.capacity(), 7);
+    }
+}
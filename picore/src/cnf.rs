@@ -0,0 +1,420 @@
+use crate::cell::CellValue;
+use crate::{Board, Cell, Constraint, Geometry, ParseError, Puzzle};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A formula in conjunctive normal form, as read by SAT solvers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cnf {
+    /// The number of variables, which are numbered from 1.
+    pub variables: usize,
+    /// The clauses, each of which needs at least one true literal. A literal is a variable's
+    /// number if it is true, or its negation if it is false.
+    pub clauses: Vec<Vec<i32>>,
+}
+
+impl fmt::Display for Cnf {
+    /// Writes the formula in the DIMACS format.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "p cnf {} {}", self.variables, self.clauses.len())?;
+        for clause in &self.clauses {
+            for literal in clause {
+                write!(f, "{} ", literal)?;
+            }
+            writeln!(f, "0")?;
+        }
+        Ok(())
+    }
+}
+
+/// An error from encoding a puzzle as CNF, because the puzzle has something which can't be encoded yet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CnfError {
+    /// What can't be encoded.
+    pub message: String,
+}
+
+impl CnfError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for CnfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl core::error::Error for CnfError {}
+
+/// A puzzle encoded as CNF, which can turn models of the formula back into boards.
+///
+/// There is one variable for each cell and value, numbered in row-major order, which is
+/// true if the cell is filled with that value. The remaining variables are true when a
+/// block starts at a certain cell.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CnfEncoding<C: CellValue> {
+    geometry: Geometry,
+    palette: Vec<C>,
+    width: usize,
+    height: usize,
+    cnf: Cnf,
+}
+
+impl<C: CellValue> CnfEncoding<C> {
+    /// Returns the formula, which can be written in the DIMACS format with `to_string`.
+    pub fn cnf(&self) -> &Cnf {
+        &self.cnf
+    }
+
+    /// Returns the variable which is true if the cell at `row` and `col` is filled with `value`,
+    /// if `value` is used by the puzzle.
+    pub fn variable(&self, row: usize, col: usize, value: C) -> Option<i32> {
+        let index = self.palette.iter().position(|v| *v == value)?;
+        Some(cell_variable(row * self.width + col, index, self.palette.len()))
+    }
+
+    /// Turns a model of the formula, given as its true and false literals, into a solved board.
+    /// Variables missing from the model are false. Positions which aren't cells are left empty.
+    pub fn decode(&self, model: &[i32]) -> Board<C> {
+        let mut assigned = vec![false; self.cnf.variables + 1];
+        for &literal in model {
+            if literal > 0 && literal as usize <= self.cnf.variables {
+                assigned[literal as usize] = true;
+            }
+        }
+
+        let mut board = Board::new_empty(self.width, self.height);
+        for index in 0..self.width * self.height {
+            if !self.geometry.contains(index / self.width, index % self.width) {
+                continue;
+            }
+            let value =
+                (0..self.palette.len()).find(|&k| assigned[cell_variable(index, k, self.palette.len()) as usize]);
+            *board.get_mut(index / self.width, index % self.width) = match value {
+                Some(k) => Cell::Filled(self.palette[k]),
+                None => Cell::CrossedOut,
+            };
+        }
+        board
+    }
+
+    /// Reads the output of a SAT solver, either in the competition format (`s` and `v` lines)
+    /// or as written by MiniSat, and decodes its model. Returns `None` if the formula is
+    /// unsatisfiable, meaning the puzzle has no solution.
+    ///
+    /// Returns an error if the solver didn't decide, for example `s UNKNOWN` after a timeout,
+    /// or if the output has neither a status nor a model.
+    pub fn parse_model(&self, output: &str) -> Result<Option<Board<C>>, ParseError> {
+        let mut model = Vec::new();
+        let mut satisfiable = false;
+        let mut last_line = 1;
+        for (number, line) in output.lines().enumerate().map(|(i, line)| (i + 1, line.trim())) {
+            last_line = number;
+            let values = match line {
+                "" => continue,
+                "s UNSATISFIABLE" | "UNSAT" => return Ok(None),
+                "s SATISFIABLE" | "SAT" => {
+                    satisfiable = true;
+                    continue;
+                }
+                _ if line.starts_with('s') => {
+                    return Err(ParseError::new(number, format!("the solver didn't decide: `{}`", line)))
+                }
+                _ if line.starts_with('c') => continue,
+                _ => line.strip_prefix('v').unwrap_or(line),
+            };
+            for value in values.split_whitespace() {
+                let literal = value
+                    .parse::<i32>()
+                    .map_err(|_| ParseError::new(number, format!("expected a literal, found `{}`", value)))?;
+                model.push(literal);
+            }
+        }
+        if !satisfiable && model.is_empty() {
+            return Err(ParseError::new(last_line, "expected a status or a model"));
+        }
+        Ok(Some(self.decode(&model)))
+    }
+}
+
+impl<C: CellValue> Puzzle<C> {
+    /// Encodes this puzzle as CNF, so it can be solved by an external SAT solver.
+    ///
+    /// Returns an error if the puzzle has mega clues, wrapping lines, or total or count clues,
+    /// which can't be encoded yet.
+    pub fn to_cnf(&self) -> Result<CnfEncoding<C>, CnfError> {
+        if !self.mega_clues().is_empty() {
+            return Err(CnfError::new("puzzles with mega clues can't be encoded as CNF"));
+        }
+        if self.wraps() {
+            return Err(CnfError::new("puzzles with wrapping lines can't be encoded as CNF"));
+        }
+        if !self.clue_kinds().is_empty() {
+            return Err(CnfError::new(
+                "puzzles with total or count clues can't be encoded as CNF",
+            ));
+        }
+        let palette = self.palette();
+        let geometry = *self.geometry();
+        let (width, height) = geometry.dimensions();
+        let mut encoder = Encoder {
+            colours: palette.len(),
+            cnf: Cnf {
+                variables: width * height * palette.len(),
+                clauses: Vec::new(),
+            },
+        };
+
+        for index in 0..width * height {
+            if !geometry.contains(index / width, index % width) {
+                for colour in 0..palette.len() {
+                    encoder.clause(vec![-cell_variable(index, colour, palette.len())]);
+                }
+                continue;
+            }
+            for a in 0..palette.len() {
+                for b in (a + 1)..palette.len() {
+                    encoder.clause(vec![
+                        -cell_variable(index, a, palette.len()),
+                        -cell_variable(index, b, palette.len()),
+                    ]);
+                }
+            }
+        }
+        for &(row, col, given) in self.givens() {
+            let index = row * width + col;
+            match given {
+                Some(value) => {
+                    let colour = palette.iter().position(|v| *v == value).unwrap();
+                    encoder.clause(vec![cell_variable(index, colour, palette.len())]);
+                }
+                None => {
+                    for colour in 0..palette.len() {
+                        encoder.clause(vec![-cell_variable(index, colour, palette.len())]);
+                    }
+                }
+            }
+        }
+        for line in geometry.lines().filter(|&line| self.is_clue_known(line)) {
+            let cells = geometry
+                .line(line)
+                .into_iter()
+                .map(|(row, col)| row * width + col)
+                .collect::<Vec<_>>();
+            encoder.line(&cells, &blocks(self.constraint(line), &palette));
+        }
+
+        Ok(CnfEncoding {
+            geometry,
+            palette,
+            width,
+            height,
+            cnf: encoder.cnf,
+        })
+    }
+}
+
+fn cell_variable(index: usize, colour: usize, colours: usize) -> i32 {
+    (index * colours + colour + 1) as i32
+}
+
+/// Returns the sizes and palette indices of a constraint's blocks.
+fn blocks<C: CellValue>(constraint: &Constraint<C>, palette: &[C]) -> Vec<(usize, usize)> {
+    constraint
+        .iter()
+        .filter(|entry| entry.size > 0)
+        .map(|entry| (entry.size, palette.iter().position(|v| *v == entry.value).unwrap()))
+        .collect()
+}
+
+struct Encoder {
+    colours: usize,
+    cnf: Cnf,
+}
+
+impl Encoder {
+    fn variable(&mut self) -> i32 {
+        self.cnf.variables += 1;
+        self.cnf.variables as i32
+    }
+
+    fn clause(&mut self, clause: Vec<i32>) {
+        self.cnf.clauses.push(clause);
+    }
+
+    /// Encodes the line made of `cells`, which must contain `blocks` in order.
+    fn line(&mut self, cells: &[usize], blocks: &[(usize, usize)]) {
+        let n = cells.len();
+        let gap = |j: usize| (j + 1 < blocks.len() && blocks[j].1 == blocks[j + 1].1) as usize;
+
+        // Every block starts somewhere between where the blocks packed left and right put it.
+        let mut starts = Vec::with_capacity(blocks.len());
+        let mut earliest = 0;
+        let mut latest = n as isize - (0..blocks.len()).map(|j| blocks[j].0 + gap(j)).sum::<usize>() as isize;
+        for (j, &(size, _)) in blocks.iter().enumerate() {
+            if latest < earliest as isize {
+                self.clause(Vec::new());
+                return;
+            }
+            let variables = (earliest..=latest as usize)
+                .map(|p| (p, self.variable()))
+                .collect::<Vec<_>>();
+            earliest += size + gap(j);
+            latest += (size + gap(j)) as isize;
+            starts.push(variables);
+        }
+
+        for (j, variables) in starts.iter().enumerate() {
+            let (size, colour) = blocks[j];
+            self.clause(variables.iter().map(|&(_, v)| v).collect());
+            for (a, &(_, first)) in variables.iter().enumerate() {
+                for &(_, second) in &variables[a + 1..] {
+                    self.clause(vec![-first, -second]);
+                }
+            }
+            if let Some(next) = starts.get(j + 1) {
+                for &(p, first) in variables {
+                    for &(q, second) in next {
+                        if q < p + size + gap(j) {
+                            self.clause(vec![-first, -second]);
+                        }
+                    }
+                }
+            }
+            for &(p, v) in variables {
+                for &cell in &cells[p..p + size] {
+                    self.clause(vec![-v, cell_variable(cell, colour, self.colours)]);
+                }
+            }
+        }
+
+        // A filled cell must be covered by a block of its value.
+        for (i, &cell) in cells.iter().enumerate() {
+            for colour in 0..self.colours {
+                let mut clause = vec![-cell_variable(cell, colour, self.colours)];
+                for (j, variables) in starts.iter().enumerate() {
+                    if blocks[j].1 == colour {
+                        clause.extend(
+                            variables
+                                .iter()
+                                .filter(|&&(p, _)| p <= i && i < p + blocks[j].0)
+                                .map(|&(_, v)| v),
+                        );
+                    }
+                }
+                self.clause(clause);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{constraints, Board, Cnf, Puzzle, SimpleCell};
+
+    /// Finds every model of `cnf` by trying every assignment.
+    fn models(cnf: &Cnf) -> Vec<Vec<i32>> {
+        (0..1u32 << cnf.variables)
+            .map(|bits| {
+                (1..=cnf.variables as i32)
+                    .map(|v| if bits & (1 << (v - 1)) != 0 { v } else { -v })
+                    .collect::<Vec<_>>()
+            })
+            .filter(|model| {
+                cnf.clauses.iter().all(|clause| {
+                    clause
+                        .iter()
+                        .any(|literal| model[literal.unsigned_abs() as usize - 1] == *literal)
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn models_are_solutions() {
+        let puzzle = Puzzle::new(
+            constraints![[1, SimpleCell][1, SimpleCell]],
+            constraints![[1, SimpleCell][1, SimpleCell]],
+        );
+        let encoding = puzzle.to_cnf().unwrap();
+
+        let boards = models(encoding.cnf())
+            .iter()
+            .map(|model| encoding.decode(model))
+            .collect::<Vec<Board<_>>>();
+        assert_eq!(boards.len(), 2);
+        assert!(boards.iter().all(|board| puzzle.is_solved_by(board)));
+    }
+
+    #[test]
+    fn impossible_puzzles_are_unsatisfiable() {
+        let puzzle = Puzzle::new(constraints![[2, SimpleCell][]], constraints![[1, SimpleCell][]]);
+        assert!(models(puzzle.to_cnf().unwrap().cnf()).is_empty());
+
+        let puzzle = Puzzle::new(
+            constraints![[2, SimpleCell; 1, SimpleCell]],
+            constraints![[1, SimpleCell]],
+        );
+        assert!(puzzle.to_cnf().unwrap().cnf().to_string().contains("\n0\n"));
+    }
+
+    #[test]
+    fn refuses_unsupported_variants() {
+        let puzzle = Puzzle::new(constraints![[1, SimpleCell]], constraints![[1, SimpleCell]]);
+        assert!(puzzle.clone().with_wrapping(true).to_cnf().is_err());
+        assert!(puzzle.to_cnf().is_ok());
+    }
+
+    #[test]
+    fn parses_solver_output() {
+        let puzzle = Puzzle::new(constraints![[1, SimpleCell] []], constraints![[1, SimpleCell]]);
+        let encoding = puzzle.to_cnf().unwrap();
+        let filled = encoding.variable(0, 0, SimpleCell).unwrap();
+
+        let board = encoding
+            .parse_model(&format!("c comment\ns SATISFIABLE\nv {} -2\nv 0\n", filled))
+            .unwrap()
+            .unwrap();
+        assert!(puzzle.is_solved_by(&board));
+        assert_eq!(encoding.parse_model("UNSAT\n"), Ok(None));
+        assert_eq!(encoding.parse_model("v 1 x 0").unwrap_err().line, 1);
+    }
+
+    #[test]
+    fn rejects_undecided_output() {
+        let puzzle = Puzzle::new(constraints![[1, SimpleCell] []], constraints![[1, SimpleCell]]);
+        let encoding = puzzle.to_cnf().unwrap();
+
+        assert_eq!(
+            encoding
+                .parse_model(
+                    "c timed out
+s UNKNOWN
+"
+                )
+                .unwrap_err()
+                .line,
+            2
+        );
+        assert!(encoding.parse_model("s TIMEOUT").is_err());
+        assert_eq!(
+            encoding
+                .parse_model(
+                    "c nothing
+
+"
+                )
+                .unwrap_err()
+                .line,
+            2
+        );
+        assert!(encoding.parse_model("").is_err());
+    }
+}
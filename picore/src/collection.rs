@@ -0,0 +1,417 @@
+use crate::cell::CellValue;
+use crate::non::{parse_lines, parse_metadata, parse_number, write_metadata, write_non, ParseError, WriteError};
+use crate::{Board, Cell, Metadata, Puzzle, SimpleCell};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+/// A player's progress on a single puzzle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Progress<C: CellValue> {
+    /// Whether or not the puzzle has ever been solved.
+    pub completed: bool,
+    /// The fastest time the puzzle was solved in.
+    pub best_time: Option<Duration>,
+    /// The board of an unfinished game, to be resumed later.
+    pub saved: Option<Board<C>>,
+    /// The time spent on the unfinished game so far.
+    pub elapsed: Duration,
+}
+
+impl<C: CellValue> Default for Progress<C> {
+    fn default() -> Self {
+        Self {
+            completed: false,
+            best_time: None,
+            saved: None,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+/// A puzzle in a collection, and the player's progress on it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry<C: CellValue> {
+    /// The puzzle.
+    pub puzzle: Puzzle<C>,
+    /// The player's progress on the puzzle.
+    pub progress: Progress<C>,
+}
+
+/// An ordered group of puzzles, such as a puzzle pack, with the player's progress on each.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Collection<C: CellValue> {
+    metadata: Metadata,
+    entries: Vec<Entry<C>>,
+}
+
+impl<C: CellValue> Collection<C> {
+    /// Creates an empty collection described by `metadata`.
+    pub fn new(metadata: Metadata) -> Self {
+        Self {
+            metadata,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns this collection's metadata.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Returns a mutable reference to this collection's metadata.
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    /// Adds `puzzle` to the end of this collection, with no progress.
+    pub fn push(&mut self, puzzle: Puzzle<C>) {
+        self.entries.push(Entry {
+            puzzle,
+            progress: Progress::default(),
+        });
+    }
+
+    /// The number of puzzles in this collection.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether or not this collection has no puzzles.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the entry at `index`.
+    pub fn get(&self, index: usize) -> Option<&Entry<C>> {
+        self.entries.get(index)
+    }
+
+    /// Returns an iterator over the entries in this collection, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &Entry<C>> {
+        self.entries.iter()
+    }
+
+    /// The number of puzzles which have been solved.
+    pub fn completed(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.progress.completed).count()
+    }
+
+    /// Returns the index of the first unsolved puzzle, if any.
+    pub fn next_unsolved(&self) -> Option<usize> {
+        self.entries.iter().position(|entry| !entry.progress.completed)
+    }
+
+    /// Records that the puzzle at `index` was solved in `time`, and discards its saved game.
+    /// Returns whether or not `time` is a new best.
+    pub fn record_solve(&mut self, index: usize, time: Duration) -> bool {
+        let progress = &mut self.entries[index].progress;
+        progress.completed = true;
+        progress.saved = None;
+        progress.elapsed = Duration::ZERO;
+
+        let best = progress.best_time.is_none_or(|best| time < best);
+        if best {
+            progress.best_time = Some(time);
+        }
+        best
+    }
+
+    /// Saves `board` as the unfinished game for the puzzle at `index`, which has been played for `elapsed`.
+    pub fn save_game(&mut self, index: usize, board: Board<C>, elapsed: Duration) {
+        let progress = &mut self.entries[index].progress;
+        progress.saved = Some(board);
+        progress.elapsed = elapsed;
+    }
+}
+
+impl Collection<SimpleCell> {
+    /// Loads every `.non` file in the directory at `path`, ordered by file name.
+    /// The collection is titled with the directory's name.
+    #[cfg(feature = "std")]
+    pub fn load_dir(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+
+        let mut files = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        files.retain(|file| file.extension().is_some_and(|extension| extension == "non"));
+        files.sort();
+
+        let mut collection = Collection::new(Metadata {
+            title: path.file_name().map(|name| name.to_string_lossy().into_owned()),
+            ..Metadata::default()
+        });
+        for file in files {
+            let puzzle = crate::parse_non(&fs::read_to_string(&file)?).map_err(|error| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", file.display(), error))
+            })?;
+            collection.push(puzzle);
+        }
+        Ok(collection)
+    }
+
+    /// Loads a pack file from `path`. See [`parse_pack`](Collection::parse_pack) for the format.
+    #[cfg(feature = "std")]
+    pub fn load_pack(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse_pack(&fs::read_to_string(path)?).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Parses a pack: the collection's metadata, written like a `.non` file's, followed by
+    /// its puzzles in `.non` format, each starting with a line containing only `puzzle`.
+    pub fn parse_pack(text: &str) -> Result<Self, ParseError> {
+        let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line)).peekable();
+
+        let mut header = Vec::new();
+        while let Some(line) = lines.next_if(|(_, line)| line.trim() != "puzzle") {
+            header.push(line);
+        }
+
+        let mut collection = Collection::new(parse_metadata(&mut header.into_iter())?);
+        while let Some((start, _)) = lines.next() {
+            let mut section = Vec::new();
+            while let Some(line) = lines.next_if(|(_, line)| line.trim() != "puzzle") {
+                section.push(line);
+            }
+            if section.is_empty() {
+                return Err(ParseError::new(start, "empty puzzle"));
+            }
+            collection.push(parse_lines(&mut section.into_iter())?);
+        }
+        Ok(collection)
+    }
+
+    /// Writes this collection as a pack. Progress is not included, but can be saved with
+    /// [`write_progress`](Collection::write_progress).
+    /// Returns an error if any of its puzzles can't be written in the `.non` format.
+    pub fn write_pack(&self) -> Result<String, WriteError> {
+        let mut text = String::new();
+        write_metadata(&mut text, &self.metadata);
+        for entry in &self.entries {
+            text.push_str("\npuzzle\n");
+            text.push_str(&write_non(&entry.puzzle)?);
+        }
+        Ok(text)
+    }
+
+    /// Writes the player's progress on this collection, to be read back by
+    /// [`read_progress`](Collection::read_progress). Packs don't include progress, so it is saved separately.
+    ///
+    /// Each puzzle with any progress has a section starting with `entry` and its index. It is followed by
+    /// `completed` if the puzzle has been solved, `best` and its best time in milliseconds, and `saved` and the
+    /// time spent on its unfinished game in milliseconds, followed by a line for each row of that game's board.
+    /// Cells are written as `.` (empty), `/` (crossed out), `#` (filled), `?` (marked) or `+` (tentative).
+    /// Locked cells aren't recorded, but givens are locked again when a game is resumed.
+    pub fn write_progress(&self) -> String {
+        let mut text = String::new();
+        for (index, entry) in self.entries.iter().enumerate() {
+            let progress = &entry.progress;
+            if *progress == Progress::default() {
+                continue;
+            }
+            writeln!(text, "entry {}", index).unwrap();
+            if progress.completed {
+                text.push_str("completed\n");
+            }
+            if let Some(best_time) = progress.best_time {
+                writeln!(text, "best {}", best_time.as_millis()).unwrap();
+            }
+            if let Some(board) = &progress.saved {
+                writeln!(text, "saved {}", progress.elapsed.as_millis()).unwrap();
+                for row in 0..board.height() {
+                    text.extend((0..board.width()).map(|column| cell_char(board.get(row, column))));
+                    text.push('\n');
+                }
+            }
+        }
+        text
+    }
+
+    /// Replaces the player's progress on this collection with progress written by
+    /// [`write_progress`](Collection::write_progress). Nothing changes if the progress can't be read.
+    pub fn read_progress(&mut self, text: &str) -> Result<(), ParseError> {
+        let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line));
+        let mut read = vec![Progress::default(); self.entries.len()];
+        let mut index = None;
+
+        while let Some((number, line)) = lines.next() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = match line.find(char::is_whitespace) {
+                Some(split) => (&line[..split], line[split..].trim()),
+                None => (line, ""),
+            };
+            if key == "entry" {
+                let entry: usize = parse_number(number, value)?;
+                if entry >= read.len() {
+                    return Err(ParseError::new(number, format!("there is no puzzle {}", entry)));
+                }
+                index = Some(entry);
+                continue;
+            }
+
+            let index = index.ok_or_else(|| ParseError::new(number, format!("`{}` must come after `entry`", key)))?;
+            let progress = &mut read[index];
+            match key {
+                "completed" => progress.completed = true,
+                "best" => progress.best_time = Some(Duration::from_millis(parse_number(number, value)?)),
+                "saved" => {
+                    progress.elapsed = Duration::from_millis(parse_number(number, value)?);
+                    let (width, height) = self.entries[index].puzzle.geometry().dimensions();
+                    let mut board = Board::new_empty(width, height);
+                    for row in 0..height {
+                        let (number, line) = lines
+                            .next()
+                            .ok_or_else(|| ParseError::new(number + row, format!("expected {} board lines", height)))?;
+                        let line = line.trim();
+                        if line.chars().count() != width {
+                            return Err(ParseError::new(number, format!("expected {} cells", width)));
+                        }
+                        for (column, c) in line.chars().enumerate() {
+                            *board.get_mut(row, column) = parse_cell(number, c)?;
+                        }
+                    }
+                    progress.saved = Some(board);
+                }
+                _ => return Err(ParseError::new(number, format!("unknown key `{}`", key))),
+            }
+        }
+
+        for (entry, progress) in self.entries.iter_mut().zip(read) {
+            entry.progress = progress;
+        }
+        Ok(())
+    }
+}
+
+fn cell_char(cell: &Cell<SimpleCell>) -> char {
+    match *cell {
+        Cell::Empty => '.',
+        Cell::CrossedOut => '/',
+        Cell::Filled(_) => '#',
+        Cell::Marked => '?',
+        Cell::Tentative(_) => '+',
+    }
+}
+
+fn parse_cell(line: usize, c: char) -> Result<Cell<SimpleCell>, ParseError> {
+    match c {
+        '.' => Ok(Cell::Empty),
+        '/' => Ok(Cell::CrossedOut),
+        '#' => Ok(Cell::Filled(SimpleCell)),
+        '?' => Ok(Cell::Marked),
+        '+' => Ok(Cell::Tentative(SimpleCell)),
+        c => Err(ParseError::new(line, format!("expected a cell, found `{}`", c))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use crate::write_non;
+    use crate::{constraints, Board, Cell, Collection, Metadata, Puzzle, SimpleCell};
+    #[cfg(feature = "std")]
+    use std::fs;
+    use std::time::Duration;
+
+    fn test_collection() -> Collection<SimpleCell> {
+        let mut collection = Collection::new(Metadata {
+            title: Some("Tiny pack".to_owned()),
+            author: Some("picore".to_owned()),
+            ..Metadata::default()
+        });
+        collection.push(Puzzle::new(
+            constraints![[1, SimpleCell]],
+            constraints![[1, SimpleCell]],
+        ));
+        collection.push(
+            Puzzle::new(
+                constraints![[2, SimpleCell]],
+                constraints![[1, SimpleCell][1, SimpleCell]],
+            )
+            .with_metadata(Metadata {
+                title: Some("Bar".to_owned()),
+                ..Metadata::default()
+            }),
+        );
+        collection
+    }
+
+    #[test]
+    fn pack_round_trips() {
+        let collection = test_collection();
+        assert_eq!(
+            Collection::parse_pack(&collection.write_pack().unwrap()),
+            Ok(collection)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn loads_directory() {
+        let dir = std::env::temp_dir().join(format!("picore-collection-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let collection = test_collection();
+        for (name, entry) in ["b.non", "a.non"].iter().zip(collection.iter()) {
+            fs::write(dir.join(name), write_non(&entry.puzzle).unwrap()).unwrap();
+        }
+        fs::write(dir.join("readme.txt"), "not a puzzle").unwrap();
+
+        let loaded = Collection::load_dir(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        let loaded = loaded.unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.get(0).unwrap().puzzle, collection.get(1).unwrap().puzzle);
+        assert_eq!(loaded.get(1).unwrap().puzzle, collection.get(0).unwrap().puzzle);
+    }
+
+    #[test]
+    fn tracks_progress() {
+        let mut collection = test_collection();
+        assert_eq!(collection.next_unsolved(), Some(0));
+
+        collection.save_game(0, Board::new_empty(1, 1), Duration::from_secs(5));
+        assert!(collection.get(0).unwrap().progress.saved.is_some());
+
+        assert!(collection.record_solve(0, Duration::from_secs(20)));
+        assert!(!collection.record_solve(0, Duration::from_secs(30)));
+        assert!(collection.record_solve(0, Duration::from_secs(10)));
+
+        let progress = &collection.get(0).unwrap().progress;
+        assert_eq!(progress.best_time, Some(Duration::from_secs(10)));
+        assert_eq!(progress.saved, None);
+        assert_eq!(collection.completed(), 1);
+        assert_eq!(collection.next_unsolved(), Some(1));
+    }
+
+    #[test]
+    fn progress_round_trips() {
+        let mut collection = test_collection();
+        collection.record_solve(0, Duration::from_millis(1500));
+        let mut board = Board::new_empty(2, 1);
+        *board.get_mut(0, 0) = Cell::Filled(SimpleCell);
+        *board.get_mut(0, 1) = Cell::Marked;
+        collection.save_game(1, board, Duration::from_secs(7));
+
+        let mut loaded = test_collection();
+        assert_eq!(loaded.read_progress(&collection.write_progress()), Ok(()));
+        assert_eq!(loaded, collection);
+
+        let error = loaded.read_progress("entry 1\nsaved 10\n#x\n").unwrap_err();
+        assert_eq!(error.line, 3);
+        assert_eq!(loaded, collection);
+    }
+}
@@ -0,0 +1,147 @@
+use crate::cell::CellValue;
+use crate::{Board, Cell};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A cell which differs between a board and its reference.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct CellDiff<C: CellValue> {
+    /// The row of the cell.
+    pub row: usize,
+    /// The column of the cell.
+    pub col: usize,
+    /// The cell in the compared board.
+    pub actual: Cell<C>,
+    /// The cell in the reference board.
+    pub expected: Cell<C>,
+}
+
+/// The differences between a board and a reference board, such as a solution.
+///
+/// Empty and crossed out cells are considered the same, so only differences in
+/// filled cells are reported.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Diff<C: CellValue> {
+    /// Every differing cell, in row-major order.
+    pub cells: Vec<CellDiff<C>>,
+    /// The number of cells filled where the reference has a different value or none.
+    pub wrong_fills: usize,
+    /// The number of cells left empty where the reference is filled.
+    pub missing_fills: usize,
+    /// The number of cells crossed out where the reference is filled.
+    pub wrong_crosses: usize,
+}
+
+impl<C: CellValue> Diff<C> {
+    /// Whether or not the boards have the same filled cells.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
+
+impl<C: CellValue> fmt::Display for Diff<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} differing cells ({} wrong fills, {} missing fills, {} wrong crosses)",
+            self.cells.len(),
+            self.wrong_fills,
+            self.missing_fills,
+            self.wrong_crosses
+        )?;
+        for cell in &self.cells {
+            write!(
+                f,
+                "\n  ({}, {}): expected {:?}, found {:?}",
+                cell.row, cell.col, cell.expected, cell.actual
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: CellValue> Board<C> {
+    /// Compares this board against `reference`.
+    ///
+    /// # Panics
+    /// Panics if the boards have different dimensions.
+    pub fn diff(&self, reference: &Board<C>) -> Diff<C> {
+        assert!(
+            self.width() == reference.width() && self.height() == reference.height(),
+            "cannot compare a {}x{} board with a {}x{} board",
+            self.width(),
+            self.height(),
+            reference.width(),
+            reference.height()
+        );
+
+        let mut diff = Diff {
+            cells: Vec::new(),
+            wrong_fills: 0,
+            missing_fills: 0,
+            wrong_crosses: 0,
+        };
+        for (row, col, &actual) in self.cells() {
+            let expected = *reference.get(row, col);
+            match (actual, expected) {
+                (Cell::Filled(a), Cell::Filled(e)) if a == e => continue,
+                (Cell::Filled(_), _) => diff.wrong_fills += 1,
+                (Cell::CrossedOut, Cell::Filled(_)) => diff.wrong_crosses += 1,
+                (_, Cell::Filled(_)) => diff.missing_fills += 1,
+                _ => continue,
+            }
+            diff.cells.push(CellDiff {
+                row,
+                col,
+                actual,
+                expected,
+            });
+        }
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Board, CellDiff, SimpleCell};
+
+    #[test]
+    fn diff_works() {
+        use crate::Cell::*;
+        let filled = Filled(SimpleCell);
+
+        #[rustfmt::skip]
+        let solution = Board::new_raw(vec![
+            filled,     filled, CrossedOut,
+            CrossedOut, filled, filled,
+        ], 3, 2);
+        #[rustfmt::skip]
+        let board = Board::new_raw(vec![
+            filled, CrossedOut, filled,
+            Empty,  Empty,      filled,
+        ], 3, 2);
+
+        let diff = board.diff(&solution);
+        assert_eq!(diff.wrong_fills, 1);
+        assert_eq!(diff.missing_fills, 1);
+        assert_eq!(diff.wrong_crosses, 1);
+        assert_eq!(
+            diff.cells[0],
+            CellDiff {
+                row: 0,
+                col: 1,
+                actual: CrossedOut,
+                expected: filled
+            }
+        );
+        assert_eq!(
+            diff.to_string(),
+            "3 differing cells (1 wrong fills, 1 missing fills, 1 wrong crosses)\n  \
+             (0, 1): expected Filled(SimpleCell), found CrossedOut\n  \
+             (0, 2): expected CrossedOut, found Filled(SimpleCell)\n  \
+             (1, 1): expected Filled(SimpleCell), found Empty"
+        );
+
+        assert!(solution.diff(&solution).is_empty());
+    }
+}
@@ -0,0 +1,276 @@
+//! A C API, so frontends written in other languages can share the rules of [`Picross`].
+//!
+//! Games are behind opaque [`PicoreGame`] handles, created from `.non` text and freed with
+//! [`picore_game_free`]. Rows are line family 0 and columns are family 1. The functions are
+//! declared in `include/picore.h`. After changing them, regenerate it from the `picore` directory
+//! with `cbindgen --config cbindgen.toml --output include/picore.h`; CI checks that it is up to
+//! date. To link against them, build a library with
+//! `cargo rustc -p picore --release --features ffi --crate-type staticlib` (or `cdylib`).
+
+use crate::{parse_non, Cell, LineId, LockedError, Picross, SimpleCell};
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+/// A game of a black and white puzzle.
+pub struct PicoreGame(Picross<SimpleCell>);
+
+/// What a cell of a game contains.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PicoreCell {
+    /// An empty cell.
+    Empty,
+    /// A crossed out cell.
+    CrossedOut,
+    /// A filled cell.
+    Filled,
+    /// An empty cell the player has marked as possibly filled.
+    Marked,
+    /// A cell the player has tentatively filled, while testing a guess.
+    Tentative,
+}
+
+/// The result of a move.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PicoreMove {
+    /// The move was made, and the puzzle isn't solved.
+    Unsolved,
+    /// The move was made, and the puzzle is solved.
+    Solved,
+    /// The cell is locked, so nothing changed.
+    Locked,
+    /// There is no cell there, so nothing changed.
+    OutOfBounds,
+}
+
+/// Creates a game from the NUL-terminated `.non` text `text`. Returns NULL if `text` is NULL,
+/// isn't UTF-8 or isn't a valid puzzle.
+///
+/// # Safety
+/// `text` must be NULL or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn picore_game_from_non(text: *const c_char) -> *mut PicoreGame {
+    if text.is_null() {
+        return std::ptr::null_mut();
+    }
+    match CStr::from_ptr(text).to_str().ok().and_then(|text| parse_non(text).ok()) {
+        Some(puzzle) => Box::into_raw(Box::new(PicoreGame(Picross::new(puzzle)))),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Frees `game`. Does nothing if it is NULL.
+///
+/// # Safety
+/// `game` must be NULL or a game from [`picore_game_from_non`] which hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn picore_game_free(game: *mut PicoreGame) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+/// The number of columns in `game`.
+///
+/// # Safety
+/// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn picore_game_width(game: *const PicoreGame) -> usize {
+    (*game).0.width()
+}
+
+/// The number of rows in `game`.
+///
+/// # Safety
+/// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn picore_game_height(game: *const PicoreGame) -> usize {
+    (*game).0.height()
+}
+
+/// Fills the cell at `row` and `column`.
+///
+/// # Safety
+/// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn picore_game_place(game: *mut PicoreGame, row: usize, column: usize) -> PicoreMove {
+    make_move(&mut *game, row, column, |picross| {
+        picross.place_at(SimpleCell, row, column)
+    })
+}
+
+/// Crosses out the cell at `row` and `column`.
+///
+/// # Safety
+/// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn picore_game_cross_out(game: *mut PicoreGame, row: usize, column: usize) -> PicoreMove {
+    make_move(&mut *game, row, column, |picross| picross.cross_out(row, column))
+}
+
+/// Empties the cell at `row` and `column`.
+///
+/// # Safety
+/// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn picore_game_clear(game: *mut PicoreGame, row: usize, column: usize) -> PicoreMove {
+    make_move(&mut *game, row, column, |picross| picross.clear_at(row, column))
+}
+
+/// Returns the cell at `row` and `column`, or [`PicoreCell::Empty`] if there is none.
+///
+/// # Safety
+/// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn picore_game_cell(game: *const PicoreGame, row: usize, column: usize) -> PicoreCell {
+    let picross = &(*game).0;
+    if !contains(picross, row, column) {
+        return PicoreCell::Empty;
+    }
+    match picross.get(row, column) {
+        Cell::Empty => PicoreCell::Empty,
+        Cell::CrossedOut => PicoreCell::CrossedOut,
+        Cell::Filled(_) => PicoreCell::Filled,
+        Cell::Marked => PicoreCell::Marked,
+        Cell::Tentative(_) => PicoreCell::Tentative,
+    }
+}
+
+/// Writes the block sizes of line `index` in `family` to `sizes`, which has room for `len` of
+/// them. Returns the number of blocks, which may be more than `len`, or 0 if there is no such
+/// line.
+///
+/// # Safety
+/// `game` must be a game from [`picore_game_from_non`] which hasn't been freed, and `sizes` must
+/// point to `len` writable values, or may be NULL if `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn picore_game_clue(
+    game: *const PicoreGame,
+    family: usize,
+    index: usize,
+    sizes: *mut usize,
+    len: usize,
+) -> usize {
+    let picross = &(*game).0;
+    let constraint = match line(picross, family, index) {
+        Some(_) if family == 0 => &picross.row_constraints()[index],
+        Some(_) => &picross.column_constraints()[index],
+        None => return 0,
+    };
+    for (i, entry) in constraint.iter().take(len).enumerate() {
+        *sizes.add(i) = entry.size;
+    }
+    constraint.len()
+}
+
+/// Whether or not line `index` in `family` satisfies its clue. Returns false if there is no
+/// such line.
+///
+/// # Safety
+/// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn picore_game_is_line_solved(game: *const PicoreGame, family: usize, index: usize) -> bool {
+    let picross = &(*game).0;
+    line(picross, family, index).is_some_and(|line| picross.is_line_solved(line))
+}
+
+/// Whether or not `game` is solved.
+///
+/// # Safety
+/// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn picore_game_is_solved(game: *const PicoreGame) -> bool {
+    (*game).0.is_solved()
+}
+
+/// Undoes the last move. Returns whether or not there was a move to undo.
+///
+/// # Safety
+/// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn picore_game_undo(game: *mut PicoreGame) -> bool {
+    (*game).0.undo()
+}
+
+/// Redoes the last undone move. Returns whether or not there was a move to redo.
+///
+/// # Safety
+/// `game` must be a game from [`picore_game_from_non`] which hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn picore_game_redo(game: *mut PicoreGame) -> bool {
+    (*game).0.redo()
+}
+
+fn contains(picross: &Picross<SimpleCell>, row: usize, column: usize) -> bool {
+    picross.geometry().contains(row, column)
+}
+
+fn line(picross: &Picross<SimpleCell>, family: usize, index: usize) -> Option<LineId> {
+    let geometry = picross.geometry();
+    Some(LineId::new(family, index)).filter(|_| family < geometry.families() && index < geometry.line_count(family))
+}
+
+/// Makes a move at `row` and `column`, checking first that there is a cell there, since
+/// panicking across the C API would abort.
+fn make_move(
+    game: &mut PicoreGame,
+    row: usize,
+    column: usize,
+    action: impl FnOnce(&mut Picross<SimpleCell>) -> Result<bool, LockedError>,
+) -> PicoreMove {
+    if !contains(&game.0, row, column) {
+        return PicoreMove::OutOfBounds;
+    }
+    match action(&mut game.0) {
+        Ok(true) => PicoreMove::Solved,
+        Ok(false) => PicoreMove::Unsolved,
+        Err(_) => PicoreMove::Locked,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+    use std::ptr;
+
+    // #.
+    // ##
+    const PUZZLE: &str = "width 2\nheight 2\nrows\n1\n2\ncolumns\n2\n1\n";
+
+    #[test]
+    fn plays_through_c_api() {
+        let text = CString::new(PUZZLE).unwrap();
+        unsafe {
+            let game = picore_game_from_non(text.as_ptr());
+            assert!(!game.is_null());
+            assert_eq!((picore_game_width(game), picore_game_height(game)), (2, 2));
+
+            let mut sizes = [0; 2];
+            assert_eq!(picore_game_clue(game, 1, 0, sizes.as_mut_ptr(), sizes.len()), 1);
+            assert_eq!(sizes, [2, 0]);
+            assert_eq!(picore_game_clue(game, 2, 0, ptr::null_mut(), 0), 0);
+
+            assert_eq!(picore_game_place(game, 0, 0), PicoreMove::Unsolved);
+            assert_eq!(picore_game_place(game, 1, 0), PicoreMove::Unsolved);
+            assert!(picore_game_is_line_solved(game, 1, 0));
+            assert_eq!(picore_game_cross_out(game, 0, 1), PicoreMove::Unsolved);
+            assert_eq!(picore_game_place(game, 2, 0), PicoreMove::OutOfBounds);
+            assert_eq!(picore_game_place(game, 1, 1), PicoreMove::Solved);
+            assert!(picore_game_is_solved(game));
+
+            assert!(picore_game_undo(game));
+            assert_eq!(picore_game_cell(game, 1, 1), PicoreCell::Empty);
+            assert!(!picore_game_is_solved(game));
+            assert!(picore_game_redo(game));
+            assert_eq!(picore_game_cell(game, 1, 1), PicoreCell::Filled);
+            picore_game_free(game);
+
+            let invalid = CString::new("width two").unwrap();
+            assert!(picore_game_from_non(invalid.as_ptr()).is_null());
+            assert!(picore_game_from_non(ptr::null()).is_null());
+            picore_game_free(ptr::null_mut());
+        }
+    }
+}
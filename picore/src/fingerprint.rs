@@ -0,0 +1,254 @@
+use crate::cell::CellValue;
+use crate::{Geometry, Puzzle};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+
+/// 64-bit FNV-1a. Unlike the standard library's hasher, its output is fixed, and
+/// integers are written little-endian, so fingerprints are the same everywhere.
+struct Fnv64(u64);
+
+impl Fnv64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    fn new() -> Self {
+        Fnv64(Self::OFFSET_BASIS)
+    }
+}
+
+impl Hasher for Fnv64 {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+}
+
+impl<C: CellValue + Hash> Puzzle<C> {
+    /// Returns a fingerprint of this puzzle's constraints. Metadata is not included.
+    ///
+    /// Fingerprints are stable across runs and platforms, provided `C`'s [`Hash`]
+    /// implementation is.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Fnv64::new();
+        for group in self.constraint_groups() {
+            hasher.write_usize(group.len());
+            for constraint in group.iter() {
+                hasher.write_usize(constraint.len());
+                for entry in constraint {
+                    hasher.write_usize(entry.size);
+                    entry.value.hash(&mut hasher);
+                }
+            }
+        }
+        // Variants are only hashed when used, so ordinary puzzles keep their fingerprints. A square
+        // grid's dimensions are already given by the number of rows and columns.
+        match *self.geometry() {
+            Geometry::Square { .. } => {}
+            Geometry::Triangle { size } => {
+                hasher.write_u8(1);
+                hasher.write_usize(size);
+            }
+            Geometry::Hexagon { radius } => {
+                hasher.write_u8(2);
+                hasher.write_usize(radius);
+            }
+        }
+        if !self.mega_clues().is_empty() {
+            hasher.write_usize(self.mega_clues().len());
+            for clue in self.mega_clues() {
+                hasher.write_usize(clue.line.family);
+                hasher.write_usize(clue.line.index);
+                hasher.write_usize(clue.size);
+                clue.value.hash(&mut hasher);
+            }
+        }
+        if self.wraps() {
+            hasher.write_u8(1);
+        }
+        if !self.unknown_clues().is_empty() {
+            hasher.write_usize(self.unknown_clues().len());
+            for line in self.unknown_clues() {
+                hasher.write_usize(line.family);
+                hasher.write_usize(line.index);
+            }
+        }
+        if !self.givens().is_empty() {
+            hasher.write_usize(self.givens().len());
+            for (row, col, given) in self.givens() {
+                hasher.write_usize(*row);
+                hasher.write_usize(*col);
+                given.hash(&mut hasher);
+            }
+        }
+        if !self.clue_kinds().is_empty() {
+            hasher.write_usize(self.clue_kinds().len());
+            for &(line, kind) in self.clue_kinds() {
+                hasher.write_usize(line.family);
+                hasher.write_usize(line.index);
+                hasher.write_u8(kind as u8);
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Returns a fingerprint which is the same for every rotation and reflection of this puzzle.
+    pub fn canonical_fingerprint(&self) -> u64 {
+        self.symmetries()
+            .iter()
+            .map(|&transform| self.transformed(transform).fingerprint())
+            .min()
+            .expect("there is always a transform")
+    }
+
+    /// Whether or not `other`'s constraints are a rotation or reflection of this puzzle's.
+    pub fn is_symmetric_to(&self, other: &Self) -> bool {
+        self.symmetries()
+            .iter()
+            .any(|&transform| self.transformed(transform).has_same_constraints(other))
+    }
+}
+
+/// Finds groups of puzzles with identical constraints, regardless of metadata. If `up_to_symmetry`
+/// is set, puzzles which are rotations or reflections of each other are also considered identical.
+///
+/// Returns the indices of each group with more than one puzzle, in order of first appearance.
+pub fn find_duplicates<'a, C, I>(puzzles: I, up_to_symmetry: bool) -> Vec<Vec<usize>>
+where
+    C: CellValue + Hash + 'a,
+    I: IntoIterator<Item = &'a Puzzle<C>>,
+{
+    let puzzles = puzzles.into_iter().collect::<Vec<_>>();
+
+    let mut buckets = BTreeMap::<u64, Vec<usize>>::new();
+    for (i, puzzle) in puzzles.iter().enumerate() {
+        let fingerprint = if up_to_symmetry {
+            puzzle.canonical_fingerprint()
+        } else {
+            puzzle.fingerprint()
+        };
+        buckets.entry(fingerprint).or_default().push(i);
+    }
+
+    // Split each bucket by actual equality, in case of collisions.
+    let mut groups = Vec::new();
+    for bucket in buckets.into_values() {
+        let mut remaining = bucket;
+        while let Some((&first, rest)) = remaining.split_first() {
+            let (same, different): (Vec<usize>, Vec<usize>) = rest.iter().partition(|&&i| {
+                if up_to_symmetry {
+                    puzzles[first].is_symmetric_to(puzzles[i])
+                } else {
+                    puzzles[first].has_same_constraints(puzzles[i])
+                }
+            });
+            if !same.is_empty() {
+                groups.push(core::iter::once(first).chain(same).collect::<Vec<_>>());
+            }
+            remaining = different;
+        }
+    }
+    groups.sort();
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{constraints, find_duplicates, Geometry, Metadata, Puzzle, SimpleCell, Transform};
+
+    fn test_puzzle() -> Puzzle<SimpleCell> {
+        // ##.
+        // ..#
+        Puzzle::new(
+            constraints![
+                [2, SimpleCell]
+                [1, SimpleCell]
+            ],
+            constraints![
+                [1, SimpleCell]
+                [1, SimpleCell]
+                [1, SimpleCell]
+            ],
+        )
+    }
+
+    #[test]
+    fn fingerprint_is_stable() {
+        assert_eq!(test_puzzle().fingerprint(), 0xad4a_ed0b_8694_9827);
+    }
+
+    #[test]
+    fn fingerprint_includes_geometry() {
+        // Both have three families of three lines, all of them empty.
+        let groups = vec![vec![vec![]; 3]; 3];
+        let triangle = Puzzle::<SimpleCell>::from_geometry(Geometry::Triangle { size: 3 }, groups.clone());
+        let hexagon = Puzzle::<SimpleCell>::from_geometry(Geometry::Hexagon { radius: 1 }, groups);
+        assert_ne!(triangle.fingerprint(), hexagon.fingerprint());
+        assert!(find_duplicates(&[triangle, hexagon], false).is_empty());
+    }
+
+    #[test]
+    fn fingerprint_ignores_order_of_givens() {
+        let givens = vec![(0, 0, Some(SimpleCell)), (1, 2, Some(SimpleCell)), (0, 2, None)];
+        let puzzle = test_puzzle().with_givens(givens.clone());
+        let reordered = test_puzzle().with_givens(givens.into_iter().rev().collect());
+        assert_eq!(puzzle.fingerprint(), reordered.fingerprint());
+        assert!(puzzle.has_same_constraints(&reordered));
+        assert_eq!(find_duplicates(&[puzzle, reordered], false), vec![vec![0, 1]]);
+    }
+
+    #[test]
+    fn canonical_fingerprint_ignores_symmetry() {
+        let puzzle = test_puzzle();
+        for &transform in &Transform::ALL {
+            let transformed = puzzle.transformed(transform);
+            assert_eq!(transformed.canonical_fingerprint(), puzzle.canonical_fingerprint());
+        }
+        assert_ne!(
+            puzzle.transformed(Transform::Rotate90).fingerprint(),
+            puzzle.fingerprint()
+        );
+    }
+
+    #[test]
+    fn finds_duplicates() {
+        let puzzle = test_puzzle();
+        let other = Puzzle::new(constraints![[1, SimpleCell]], constraints![[1, SimpleCell]]);
+        let renamed = puzzle.clone().with_metadata(Metadata {
+            title: Some("Renamed".to_owned()),
+            ..Metadata::default()
+        });
+        let puzzles = vec![
+            puzzle.clone(),
+            other.clone(),
+            puzzle.transformed(Transform::Rotate90),
+            renamed,
+            other,
+        ];
+
+        assert_eq!(find_duplicates(&puzzles, false), vec![vec![0, 3], vec![1, 4]]);
+        assert_eq!(find_duplicates(&puzzles, true), vec![vec![0, 2, 3], vec![1, 4]]);
+    }
+}
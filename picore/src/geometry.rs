@@ -0,0 +1,243 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Identifies a line of a puzzle: its family, such as rows or columns, and its index within
+/// the family.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LineId {
+    /// The family of parallel lines this line belongs to.
+    pub family: usize,
+    /// The index of this line within its family.
+    pub index: usize,
+}
+
+impl LineId {
+    /// Creates an identifier for the line at `index` in `family`.
+    pub fn new(family: usize, index: usize) -> Self {
+        Self { family, index }
+    }
+}
+
+/// The shape of a puzzle's cells, and the lines which run through them.
+///
+/// Cells are always stored in a rectangular board of [`dimensions`](Geometry::dimensions),
+/// addressed by row and column. Some geometries leave parts of the board unused.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Geometry {
+    /// The usual grid of squares. Family 0 is the rows, read left to right, and family 1 is
+    /// the columns, read top to bottom.
+    Square {
+        /// The number of columns.
+        width: usize,
+        /// The number of rows.
+        height: usize,
+    },
+    /// A triangle split into `size * size` small triangles, as in triddlers.
+    ///
+    /// Row `r` holds `2r + 1` triangles in columns `0..=2r`, alternating between pointing up
+    /// (even columns) and down (odd columns). Family 0 is the rows, read left to right.
+    /// Families 1 and 2 are the strips parallel to the left and right edges, read top to
+    /// bottom and numbered from the left and right edges respectively.
+    Triangle {
+        /// The number of rows.
+        size: usize,
+    },
+    /// A hexagon of hexagonal cells, with `radius` rings around the centre cell.
+    ///
+    /// The cells are stored in a `(2 * radius + 1)`-square board, sheared so the three axes
+    /// of the hexagon are the rows, the columns and the anti-diagonals. The position at `row`
+    /// and `col` is a cell if `row + col` is between `radius` and `3 * radius`, which cuts
+    /// off the top left and bottom right corners. Family 0 is the rows, read left to right.
+    /// Family 1 is the columns, which run down and to the right, and family 2 is the lines
+    /// running down and to the left, numbered from the top left. Both are read top to bottom.
+    Hexagon {
+        /// The number of rings around the centre cell.
+        radius: usize,
+    },
+}
+
+impl Geometry {
+    /// The width and height of the board needed to hold every cell.
+    pub fn dimensions(&self) -> (usize, usize) {
+        match *self {
+            Geometry::Square { width, height } => (width, height),
+            Geometry::Triangle { size } => ((2 * size).saturating_sub(1), size),
+            Geometry::Hexagon { radius } => (2 * radius + 1, 2 * radius + 1),
+        }
+    }
+
+    /// The number of families of parallel lines.
+    pub fn families(&self) -> usize {
+        match self {
+            Geometry::Square { .. } => 2,
+            Geometry::Triangle { .. } | Geometry::Hexagon { .. } => 3,
+        }
+    }
+
+    /// The number of lines in `family`.
+    pub fn line_count(&self, family: usize) -> usize {
+        match *self {
+            Geometry::Square { width, height } => [height, width][family],
+            Geometry::Triangle { size } => {
+                assert!(family < 3, "a triangle has no line family {}", family);
+                size
+            }
+            Geometry::Hexagon { radius } => {
+                assert!(family < 3, "a hexagon has no line family {}", family);
+                2 * radius + 1
+            }
+        }
+    }
+
+    /// Returns every line, family by family.
+    pub fn lines(&self) -> impl Iterator<Item = LineId> + '_ {
+        (0..self.families()).flat_map(move |family| (0..self.line_count(family)).map(move |i| LineId::new(family, i)))
+    }
+
+    /// Whether or not the board position at `row` and `col` is a cell.
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        let (width, height) = self.dimensions();
+        row < height
+            && col < width
+            && match self {
+                Geometry::Square { .. } => true,
+                Geometry::Triangle { .. } => col <= 2 * row,
+                Geometry::Hexagon { radius } => (*radius..=3 * radius).contains(&(row + col)),
+            }
+    }
+
+    /// Returns the positions of the cells in `line`, in reading order.
+    pub fn line(&self, line: LineId) -> Vec<(usize, usize)> {
+        let LineId { family, index } = line;
+        match *self {
+            Geometry::Square { width, height } => match family {
+                0 => (0..width).map(|col| (index, col)).collect(),
+                1 => (0..height).map(|row| (row, index)).collect(),
+                _ => panic!("a square grid has no line family {}", family),
+            },
+            Geometry::Triangle { size } => match family {
+                0 => (0..=2 * index).map(|col| (index, col)).collect(),
+                // Each strip starts with an upwards triangle at the top, then alternates
+                // between a downwards and an upwards triangle in each row below.
+                1 => core::iter::once((index, 2 * index))
+                    .chain((index + 1..size).flat_map(|row| vec![(row, 2 * index + 1), (row, 2 * index)]))
+                    .collect(),
+                2 => core::iter::once((index, 0))
+                    .chain(
+                        (index + 1..size).flat_map(|row| vec![(row, 2 * (row - index) - 1), (row, 2 * (row - index))]),
+                    )
+                    .collect(),
+                _ => panic!("a triangle has no line family {}", family),
+            },
+            Geometry::Hexagon { radius } => {
+                // The cells of each line are those with `row + col` between `radius` and
+                // `3 * radius`, which is a contiguous range along every axis.
+                let side = 2 * radius + 1;
+                let first = radius.saturating_sub(index);
+                let last = (3 * radius - index).min(side - 1);
+                match family {
+                    0 => (first..=last).map(|col| (index, col)).collect(),
+                    1 => (first..=last).map(|row| (row, index)).collect(),
+                    2 => (index.saturating_sub(radius)..=(index + radius).min(side - 1))
+                        .map(|row| (row, index + radius - row))
+                        .collect(),
+                    _ => panic!("a hexagon has no line family {}", family),
+                }
+            }
+        }
+    }
+
+    /// A name for the lines in `family`, such as "row".
+    pub fn family_name(&self, family: usize) -> &'static str {
+        match (self, family) {
+            (_, 0) => "row",
+            (Geometry::Square { .. }, 1) => "column",
+            (Geometry::Triangle { .. }, 1) => "left diagonal",
+            (Geometry::Triangle { .. }, 2) => "right diagonal",
+            (Geometry::Hexagon { .. }, 1) => "down-right diagonal",
+            (Geometry::Hexagon { .. }, 2) => "down-left diagonal",
+            _ => "line",
+        }
+    }
+
+    /// Returns a value which writes `line` for people to read, counting from 1.
+    pub fn describe(&self, line: LineId) -> impl fmt::Display {
+        LineName(self.family_name(line.family), line.index)
+    }
+}
+
+struct LineName(&'static str, usize);
+
+impl fmt::Display for LineName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.0, self.1 + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Geometry, LineId};
+    use std::collections::HashMap;
+
+    #[test]
+    fn triangle_lines_cover_every_cell_once_per_family() {
+        let geometry = Geometry::Triangle { size: 4 };
+        assert_eq!(geometry.dimensions(), (7, 4));
+
+        for family in 0..3 {
+            let mut seen = HashMap::new();
+            for index in 0..geometry.line_count(family) {
+                let cells = geometry.line(LineId::new(family, index));
+                assert_eq!(
+                    cells.len(),
+                    [2 * index + 1, 2 * (4 - index) - 1, 2 * (4 - index) - 1][family]
+                );
+                for cell in cells {
+                    assert!(geometry.contains(cell.0, cell.1), "{:?} is not a cell", cell);
+                    assert_eq!(seen.insert(cell, index), None);
+                }
+            }
+            assert_eq!(seen.len(), 16);
+        }
+    }
+
+    #[test]
+    fn triangle_strips_are_adjacent() {
+        let geometry = Geometry::Triangle { size: 3 };
+        assert_eq!(
+            geometry.line(LineId::new(1, 0)),
+            [(0, 0), (1, 1), (1, 0), (2, 1), (2, 0)]
+        );
+        assert_eq!(
+            geometry.line(LineId::new(2, 0)),
+            [(0, 0), (1, 1), (1, 2), (2, 3), (2, 4)]
+        );
+        assert_eq!(geometry.line(LineId::new(2, 2)), [(2, 0)]);
+        assert_eq!(geometry.describe(LineId::new(2, 2)).to_string(), "right diagonal 3");
+    }
+
+    #[test]
+    fn hexagon_lines_cover_every_cell_once_per_family() {
+        let geometry = Geometry::Hexagon { radius: 2 };
+        assert_eq!(geometry.dimensions(), (5, 5));
+
+        for family in 0..3 {
+            let mut seen = HashMap::new();
+            for index in 0..geometry.line_count(family) {
+                let cells = geometry.line(LineId::new(family, index));
+                assert_eq!(cells.len(), [3, 4, 5, 4, 3][index]);
+                for cell in cells {
+                    assert!(geometry.contains(cell.0, cell.1), "{:?} is not a cell", cell);
+                    assert_eq!(seen.insert(cell, index), None);
+                }
+            }
+            assert_eq!(seen.len(), 19);
+        }
+        assert_eq!(geometry.line(LineId::new(1, 0)), [(2, 0), (3, 0), (4, 0)]);
+        assert_eq!(geometry.line(LineId::new(2, 0)), [(0, 2), (1, 1), (2, 0)]);
+        assert_eq!(geometry.line(LineId::new(2, 4)), [(2, 4), (3, 3), (4, 2)]);
+        assert!(!geometry.contains(0, 1));
+        assert!(!geometry.contains(4, 3));
+    }
+}
@@ -0,0 +1,105 @@
+use crate::cell::CellValue;
+use crate::{Board, Cell};
+
+/// Storage for the cells of a picross board.
+///
+/// Implemented by [`Board`] for any cell value, and by [`BitBoard`](crate::BitBoard)
+/// for black and white puzzles.
+pub trait Grid<C: CellValue> {
+    /// Creates a grid of empty cells with `width` columns and `height` rows.
+    fn new_empty(width: usize, height: usize) -> Self
+    where
+        Self: Sized;
+
+    /// The width of this grid.
+    fn width(&self) -> usize;
+
+    /// The height of this grid.
+    fn height(&self) -> usize;
+
+    /// Returns the cell at `row` and `col`.
+    fn cell(&self, row: usize, col: usize) -> Cell<C>;
+
+    /// Sets the cell at `row` and `col`.
+    fn set_cell(&mut self, row: usize, col: usize, cell: Cell<C>);
+
+    /// Returns an iterator over the cells of the row at `index`.
+    fn row_cells(&self, index: usize) -> GridLine<'_, C, Self>
+    where
+        Self: Sized,
+    {
+        GridLine {
+            grid: self,
+            fixed: index,
+            next: 0,
+            len: self.width(),
+            is_row: true,
+            _cell: core::marker::PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the cells of the column at `index`.
+    fn column_cells(&self, index: usize) -> GridLine<'_, C, Self>
+    where
+        Self: Sized,
+    {
+        GridLine {
+            grid: self,
+            fixed: index,
+            next: 0,
+            len: self.height(),
+            is_row: false,
+            _cell: core::marker::PhantomData,
+        }
+    }
+}
+
+/// An iterator over the cells of a row or column of a [`Grid`].
+pub struct GridLine<'a, C, G> {
+    grid: &'a G,
+    // The index of the row or column.
+    fixed: usize,
+    next: usize,
+    len: usize,
+    is_row: bool,
+    _cell: core::marker::PhantomData<C>,
+}
+
+impl<'a, C: CellValue, G: Grid<C>> Iterator for GridLine<'a, C, G> {
+    type Item = Cell<C>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.len {
+            return None;
+        }
+        let cell = if self.is_row {
+            self.grid.cell(self.fixed, self.next)
+        } else {
+            self.grid.cell(self.next, self.fixed)
+        };
+        self.next += 1;
+        Some(cell)
+    }
+}
+
+impl<C: CellValue> Grid<C> for Board<C> {
+    fn new_empty(width: usize, height: usize) -> Self {
+        Board::new_empty(width, height)
+    }
+
+    fn width(&self) -> usize {
+        Board::width(self)
+    }
+
+    fn height(&self) -> usize {
+        Board::height(self)
+    }
+
+    fn cell(&self, row: usize, col: usize) -> Cell<C> {
+        *self.get(row, col)
+    }
+
+    fn set_cell(&mut self, row: usize, col: usize, cell: Cell<C>) {
+        *self.get_mut(row, col) = cell;
+    }
+}
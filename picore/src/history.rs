@@ -0,0 +1,104 @@
+use crate::cell::CellValue;
+use crate::Cell;
+use alloc::vec::Vec;
+
+/// A change to a single cell.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Change<C: CellValue> {
+    pub row: usize,
+    pub column: usize,
+    pub before: Cell<C>,
+    pub after: Cell<C>,
+}
+
+/// A group of changes which are undone and redone together.
+pub(crate) type Action<C> = Vec<Change<C>>;
+
+/// Undo and redo stacks of actions.
+pub(crate) struct History<C: CellValue> {
+    // Changes made since the last commit.
+    pending: Action<C>,
+    undo: Vec<Action<C>>,
+    redo: Vec<Action<C>>,
+}
+
+impl<C: CellValue> History<C> {
+    /// Creates an empty history.
+    pub fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Records a change as part of the current action.
+    pub fn record(&mut self, change: Change<C>) {
+        self.pending.push(change);
+    }
+
+    /// Finishes the current action, making it undoable.
+    /// Does nothing if no changes were recorded. Returns whether or not there were any.
+    pub fn commit(&mut self) -> bool {
+        if self.pending.is_empty() {
+            return false;
+        }
+        self.undo.push(core::mem::take(&mut self.pending));
+        self.redo.clear();
+        true
+    }
+
+    /// Forgets the changes recorded since the last commit.
+    pub fn discard(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Forgets every change to the cell at `row` and `column`, and any action left without changes.
+    pub fn forget(&mut self, row: usize, column: usize) {
+        let other_cell = |change: &Change<C>| change.row != row || change.column != column;
+        self.pending.retain(other_cell);
+        for stack in [&mut self.undo, &mut self.redo] {
+            for action in stack.iter_mut() {
+                action.retain(other_cell);
+            }
+            stack.retain(|action| !action.is_empty());
+        }
+    }
+
+    /// Moves the most recent action to the redo stack and returns how many changes it has.
+    pub fn undo(&mut self) -> Option<usize> {
+        let action = self.undo.pop()?;
+        let len = action.len();
+        self.redo.push(action);
+        Some(len)
+    }
+
+    /// Moves the most recently undone action back to the undo stack and returns how many changes
+    /// it has.
+    pub fn redo(&mut self) -> Option<usize> {
+        let action = self.redo.pop()?;
+        let len = action.len();
+        self.undo.push(action);
+        Some(len)
+    }
+
+    /// Returns change `index` of the action most recently moved by [`undo`](Self::undo).
+    pub fn undone(&self, index: usize) -> Change<C> {
+        self.redo[self.redo.len() - 1][index]
+    }
+
+    /// Returns change `index` of the action most recently moved by [`redo`](Self::redo).
+    pub fn redone(&self, index: usize) -> Change<C> {
+        self.undo[self.undo.len() - 1][index]
+    }
+
+    /// Whether or not there is an action to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Whether or not there is an action to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
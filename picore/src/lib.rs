@@ -1,13 +1,62 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![deny(missing_docs)]
 
 //! Core library for picross frontends.
+//!
+//! Without the default `std` feature, the crate is `no_std` and only needs `alloc`. Budgets have
+//! no timeouts, no time passes on game timers, [`verify_all`] isn't available, and collections
+//! can't be loaded from files.
 
+extern crate alloc;
+
+mod bitboard;
 mod board;
+mod budget;
 mod cell;
+mod checker;
+mod cnf;
+mod collection;
+mod diff;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod fingerprint;
+mod geometry;
+mod grid;
+mod history;
+mod mega;
+mod metadata;
+mod non;
 mod picross;
 mod puzzle;
+mod score;
+mod solver;
+mod timer;
+mod transform;
+mod verify;
 
+pub use bitboard::BitBoard;
 pub use board::Board;
-pub use cell::Cell;
-pub use picross::Picross;
-pub use puzzle::{Constraint, ConstraintEntry, ConstraintGroup, Puzzle};
+pub use budget::{Budget, CancelToken};
+pub use cell::{Cell, SimpleCell};
+pub use cnf::{Cnf, CnfEncoding, CnfError};
+pub use collection::{Collection, Entry, Progress};
+pub use diff::{CellDiff, Diff};
+pub use fingerprint::find_duplicates;
+pub use geometry::{Geometry, LineId};
+pub use grid::{Grid, GridLine};
+pub use mega::MegaClue;
+pub use metadata::Metadata;
+pub use non::{parse_non, write_non, ParseError, WriteError};
+pub use picross::{LockedError, Picross};
+pub use puzzle::{ClueKind, Constraint, ConstraintEntry, ConstraintGroup, Puzzle};
+pub use score::{DefaultScoring, Report, Scoring, Stats};
+pub use solver::{Outcome, Search, Solution, Solutions, Solver, Step, Technique};
+pub use transform::Transform;
+#[cfg(feature = "std")]
+pub use verify::verify_all;
+pub use verify::Verification;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::vec;
+}
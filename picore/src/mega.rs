@@ -0,0 +1,170 @@
+use crate::cell::CellValue;
+use crate::{Cell, ClueKind, Constraint, LineId, Puzzle};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A clue describing a shape which spans a pair of adjacent lines, as in Mega Picross.
+///
+/// The lines `line` and the next line of its family form a band. In a band with mega clues, the
+/// filled cells of each value split into shapes: groups of cells connected to each other along
+/// the lines or across the band. Shapes with cells in both lines are mega shapes, and must match
+/// the band's mega clues in order of their first cell. Shapes which stay in one line are ordinary
+/// blocks, and must match that line's own constraint.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MegaClue<C> {
+    /// The first line of the band. The second is the next line in the same family.
+    pub line: LineId,
+    /// The value of the shape's cells.
+    pub value: C,
+    /// The number of cells in the shape.
+    pub size: usize,
+}
+
+/// A shape found in a band, which is being checked against its clues.
+struct Shape<C> {
+    value: C,
+    size: usize,
+    // Whether or not the shape has cells in each line.
+    lines: [bool; 2],
+}
+
+/// Checks whether the cells of a band's two lines satisfy the lines' `constraints` and the band's
+/// mega `clues`, given in order.
+pub(crate) fn band_is_solved<C: CellValue>(
+    constraints: [&Constraint<C>; 2],
+    clues: &[&MegaClue<C>],
+    cells: [&[Cell<C>]; 2],
+) -> bool {
+    let len = cells[0].len();
+    let value = |(line, index): (usize, usize)| match cells[line][index] {
+        Cell::Filled(value) => Some(value),
+        _ => None,
+    };
+
+    // Label each filled cell with its shape, finding shapes in order of their first cell.
+    let mut labels = [vec![None; len], vec![None; len]];
+    let mut shapes = Vec::new();
+    for index in 0..len {
+        for line in 0..2 {
+            let shape_value = match value((line, index)) {
+                Some(shape_value) if labels[line][index].is_none() => shape_value,
+                _ => continue,
+            };
+            let mut shape = Shape {
+                value: shape_value,
+                size: 0,
+                lines: [false; 2],
+            };
+            labels[line][index] = Some(shapes.len());
+            let mut stack = vec![(line, index)];
+            while let Some((line, index)) = stack.pop() {
+                shape.size += 1;
+                shape.lines[line] = true;
+                let neighbours = [
+                    index.checked_sub(1).map(|i| (line, i)),
+                    Some((line, index + 1)).filter(|&(_, i)| i < len),
+                    Some((1 - line, index)),
+                ];
+                for neighbour in neighbours.iter().flatten().copied() {
+                    if labels[neighbour.0][neighbour.1].is_none() && value(neighbour) == Some(shape_value) {
+                        labels[neighbour.0][neighbour.1] = Some(shapes.len());
+                        stack.push(neighbour);
+                    }
+                }
+            }
+            shapes.push(shape);
+        }
+    }
+
+    let is_mega = |shape: &Shape<C>| shape.lines[0] && shape.lines[1];
+    let mut megas = shapes.iter().filter(|shape| is_mega(shape));
+    let mut expected = clues.iter();
+    let clues_match = loop {
+        match (megas.next(), expected.next()) {
+            (None, None) => break true,
+            (Some(shape), Some(clue)) if shape.value == clue.value && shape.size == clue.size => continue,
+            _ => break false,
+        }
+    };
+
+    // Each line's own constraint describes what is left once the mega shapes are taken out.
+    clues_match
+        && (0..2).all(|line| {
+            let rest = (0..len).map(|index| match labels[line][index] {
+                Some(shape) if is_mega(&shapes[shape]) => Cell::Empty,
+                _ => cells[line][index],
+            });
+            Puzzle::is_solved(ClueKind::Blocks, constraints[line], rest)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{constraints, Board, Cell, LineId, MegaClue, Picross, Puzzle, SimpleCell, Solutions};
+
+    // ##.#
+    // #.#.
+    // ##..
+    fn test_puzzle() -> Puzzle<SimpleCell> {
+        Puzzle::new(
+            constraints![
+                [1, SimpleCell]
+                [1, SimpleCell]
+                [2, SimpleCell]
+            ],
+            constraints![
+                [3, SimpleCell]
+                [1, SimpleCell; 1, SimpleCell]
+                [1, SimpleCell]
+                [1, SimpleCell]
+            ],
+        )
+        .with_mega_clues(vec![MegaClue {
+            line: LineId::new(0, 0),
+            value: SimpleCell,
+            size: 3,
+        }])
+    }
+
+    fn board(rows: &[&str]) -> Board<SimpleCell> {
+        let mut board = Board::new_empty(rows[0].len(), rows.len());
+        for (row, line) in rows.iter().enumerate() {
+            for (col, c) in line.chars().enumerate() {
+                if c == '#' {
+                    *board.get_mut(row, col) = Cell::Filled(SimpleCell);
+                }
+            }
+        }
+        board
+    }
+
+    #[test]
+    fn checks_mega_shapes() {
+        let puzzle = test_puzzle();
+        assert!(puzzle.is_solved_by(&board(&["##.#", "#.#.", "##.."])));
+        // The shape in the first row doesn't reach the second, so it isn't a mega shape.
+        assert!(!puzzle.is_solved_by(&board(&["###.", "...#", "##.."])));
+        // The blocks at the end of the first two rows join into a second mega shape.
+        assert!(!puzzle.is_solved_by(&board(&["##.#", "#..#", "##.."])));
+    }
+
+    #[test]
+    fn solves_and_plays_mega_puzzles() {
+        let puzzle = test_puzzle();
+        let solution = match puzzle.verify().solutions {
+            Solutions::Unique(solution) => solution,
+            other => panic!("expected a unique solution, found {:?}", other),
+        };
+        assert!(puzzle.is_solved_by(&solution));
+
+        let mut picross = Picross::new(puzzle);
+        for (row, col) in [(0, 0), (1, 0), (2, 0), (2, 1), (0, 3)] {
+            assert!(!picross.place_at(SimpleCell, row, col).unwrap());
+        }
+        assert!(!picross.is_line_solved(LineId::new(0, 0)));
+        assert!(!picross.place_at(SimpleCell, 0, 1).unwrap());
+        assert!(!picross.is_line_solved(LineId::new(0, 1)));
+        assert!(picross.place_at(SimpleCell, 1, 2).unwrap());
+        assert!(picross.is_line_solved(LineId::new(0, 0)));
+    }
+}
@@ -0,0 +1,34 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Descriptive information about a puzzle. Every field is optional.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Metadata {
+    /// The title shown while playing.
+    pub title: Option<String>,
+    /// The puzzle's author.
+    pub author: Option<String>,
+    /// The copyright notice.
+    pub copyright: Option<String>,
+    /// The licence the puzzle is distributed under.
+    pub license: Option<String>,
+    /// Where the puzzle came from, usually a URL.
+    pub source: Option<String>,
+    /// When the puzzle was made, preferably as an ISO 8601 date.
+    pub created: Option<String>,
+    /// Free-form tags for searching and grouping.
+    pub tags: Vec<String>,
+    /// How hard the puzzle is. Higher is harder.
+    pub difficulty: Option<u32>,
+    /// The title revealed once the puzzle is solved, usually naming the picture.
+    pub reveal: Option<String>,
+    /// Any other notes.
+    pub notes: Option<String>,
+}
+
+impl Metadata {
+    /// Whether or not every field is unset.
+    pub fn is_empty(&self) -> bool {
+        *self == Metadata::default()
+    }
+}
@@ -0,0 +1,425 @@
+//! Reading and writing puzzles in the `.non` format.
+
+use crate::{Constraint, ConstraintEntry, ConstraintGroup, Geometry, Metadata, Puzzle, SimpleCell};
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+use core::str::FromStr;
+
+/// An error from parsing a puzzle file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line the error was found on, starting from 1.
+    pub line: usize,
+    /// What went wrong.
+    pub message: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+/// An error from writing a puzzle file, because the puzzle has something the format can't describe.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WriteError {
+    /// What the format can't describe.
+    pub message: String,
+}
+
+impl WriteError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl core::error::Error for WriteError {}
+
+/// Parses a black and white puzzle in the `.non` format.
+///
+/// Besides the standard `title`, `by`, `copyright` and `license` keys, the `source`,
+/// `created`, `tags`, `difficulty`, `reveal` and `notes` keys are read into the
+/// puzzle's [`Metadata`]. Tags are separated by commas, and a backslash escapes a comma in a
+/// tag. Other unknown keys, like `goal`, are ignored.
+pub fn parse_non(text: &str) -> Result<Puzzle<SimpleCell>, ParseError> {
+    parse_lines(&mut text.lines().enumerate().map(|(i, line)| (i + 1, line)))
+}
+
+/// Parses numbered lines in the `.non` format into a puzzle.
+pub(crate) fn parse_lines(lines: &mut dyn Iterator<Item = (usize, &str)>) -> Result<Puzzle<SimpleCell>, ParseError> {
+    let parts = parse_parts(lines)?;
+    match (parts.rows, parts.columns) {
+        (Some(rows), Some(columns)) => Ok(Puzzle::new(rows, columns).with_metadata(parts.metadata)),
+        (None, _) => Err(ParseError::new(parts.last_line, "missing `rows`")),
+        (_, None) => Err(ParseError::new(parts.last_line, "missing `columns`")),
+    }
+}
+
+/// Parses numbered lines in the `.non` format which only describe metadata.
+pub(crate) fn parse_metadata(lines: &mut dyn Iterator<Item = (usize, &str)>) -> Result<Metadata, ParseError> {
+    Ok(parse_parts(lines)?.metadata)
+}
+
+/// The fields of a `.non` file, which may be incomplete.
+struct Parts {
+    metadata: Metadata,
+    rows: Option<ConstraintGroup<SimpleCell>>,
+    columns: Option<ConstraintGroup<SimpleCell>>,
+    last_line: usize,
+}
+
+fn parse_parts(lines: &mut dyn Iterator<Item = (usize, &str)>) -> Result<Parts, ParseError> {
+    let mut metadata = Metadata::default();
+    let mut width = None;
+    let mut height = None;
+    let mut rows = None;
+    let mut columns = None;
+    let mut last_line = 0;
+
+    while let Some((number, line)) = lines.next() {
+        last_line = number;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = match line.find(char::is_whitespace) {
+            Some(split) => (&line[..split], line[split..].trim()),
+            None => (line, ""),
+        };
+
+        match key {
+            "width" => width = Some(parse_number(number, value)?),
+            "height" => height = Some(parse_number(number, value)?),
+            "rows" | "columns" => {
+                let count = if key == "rows" { height } else { width }.ok_or_else(|| {
+                    ParseError::new(number, format!("`{}` must come after `width` and `height`", key))
+                })?;
+                let group = parse_group(lines, count, number)?;
+                last_line = number + count;
+                if key == "rows" {
+                    rows = Some(group);
+                } else {
+                    columns = Some(group);
+                }
+            }
+            "difficulty" => metadata.difficulty = Some(parse_number(number, &unquote(number, value)?)?),
+            "tags" => metadata.tags = split_tags(&unquote(number, value)?),
+            _ => {
+                let field = match key {
+                    "title" => &mut metadata.title,
+                    "by" => &mut metadata.author,
+                    "copyright" => &mut metadata.copyright,
+                    "license" => &mut metadata.license,
+                    "source" => &mut metadata.source,
+                    "created" => &mut metadata.created,
+                    "reveal" => &mut metadata.reveal,
+                    "notes" => &mut metadata.notes,
+                    _ => continue,
+                };
+                *field = Some(unquote(number, value)?);
+            }
+        }
+    }
+
+    Ok(Parts {
+        metadata,
+        rows,
+        columns,
+        last_line,
+    })
+}
+
+pub(crate) fn parse_number<T: FromStr>(line: usize, text: &str) -> Result<T, ParseError> {
+    text.trim()
+        .parse()
+        .map_err(|_| ParseError::new(line, format!("expected a number, found `{}`", text)))
+}
+
+fn parse_group(
+    lines: &mut dyn Iterator<Item = (usize, &str)>,
+    count: usize,
+    start: usize,
+) -> Result<ConstraintGroup<SimpleCell>, ParseError> {
+    (0..count)
+        .map(|i| {
+            let (number, line) = lines
+                .next()
+                .ok_or_else(|| ParseError::new(start + i, format!("expected {} clue lines", count)))?;
+            line.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|size| !size.is_empty())
+                .map(|size| parse_number::<usize>(number, size))
+                .filter(|size| *size != Ok(0))
+                .map(|size| {
+                    size.map(|size| ConstraintEntry {
+                        value: SimpleCell,
+                        size,
+                    })
+                })
+                .collect::<Result<Constraint<_>, _>>()
+        })
+        .collect()
+}
+
+fn unquote(line: usize, value: &str) -> Result<String, ParseError> {
+    let inner = match value.strip_prefix('"') {
+        Some(rest) => rest
+            .strip_suffix('"')
+            .ok_or_else(|| ParseError::new(line, "unterminated string"))?,
+        None => return Ok(value.to_owned()),
+    };
+
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => unquoted.push('\n'),
+                Some(escaped) => unquoted.push(escaped),
+                None => return Err(ParseError::new(line, "unterminated escape")),
+            },
+            c => unquoted.push(c),
+        }
+    }
+    Ok(unquoted)
+}
+
+/// Splits a list of tags on commas, except those escaped with a backslash. Whitespace around
+/// tags is ignored, and so are empty tags.
+fn split_tags(text: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut tag = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => tag.extend(chars.next()),
+            ',' => tags.push(core::mem::take(&mut tag)),
+            c => tag.push(c),
+        }
+    }
+    tags.push(tag);
+    tags.iter()
+        .map(|tag| tag.trim())
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Joins tags with commas, escaping any commas and backslashes in them.
+fn join_tags(tags: &[String]) -> String {
+    let mut text = String::new();
+    for (i, tag) in tags.iter().enumerate() {
+        if i > 0 {
+            text.push(',');
+        }
+        for c in tag.chars() {
+            if c == ',' || c == '\\' {
+                text.push('\\');
+            }
+            text.push(c);
+        }
+    }
+    text
+}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Writes a black and white puzzle in the `.non` format, including its metadata.
+///
+/// Returns an error if the puzzle isn't on a square grid, or has mega clues, wrapping lines,
+/// unknown clues, givens or clues which aren't block clues, which the format can't describe.
+pub fn write_non(puzzle: &Puzzle<SimpleCell>) -> Result<String, WriteError> {
+    if !matches!(puzzle.geometry(), Geometry::Square { .. }) {
+        return Err(WriteError::new("only square puzzles can be written as .non"));
+    }
+    if !puzzle.mega_clues().is_empty() {
+        return Err(WriteError::new("puzzles with mega clues can't be written as .non"));
+    }
+    if puzzle.wraps() {
+        return Err(WriteError::new("puzzles with wrapping lines can't be written as .non"));
+    }
+    if !puzzle.unknown_clues().is_empty() || !puzzle.givens().is_empty() {
+        return Err(WriteError::new(
+            "puzzles with unknown clues or givens can't be written as .non",
+        ));
+    }
+    if !puzzle.clue_kinds().is_empty() {
+        return Err(WriteError::new(
+            "puzzles with total or count clues can't be written as .non",
+        ));
+    }
+    let mut text = String::new();
+    write_metadata(&mut text, puzzle.metadata());
+
+    writeln!(text, "width {}", puzzle.column_constraints().len()).unwrap();
+    writeln!(text, "height {}", puzzle.row_constraints().len()).unwrap();
+    for (key, group) in &[
+        ("rows", puzzle.row_constraints()),
+        ("columns", puzzle.column_constraints()),
+    ] {
+        writeln!(text, "\n{}", key).unwrap();
+        for constraint in group.iter() {
+            if constraint.is_empty() {
+                text.push('0');
+            }
+            let sizes = constraint
+                .iter()
+                .map(|entry| entry.size.to_string())
+                .collect::<Vec<_>>();
+            text.push_str(&sizes.join(","));
+            text.push('\n');
+        }
+    }
+    Ok(text)
+}
+
+pub(crate) fn write_metadata(text: &mut String, metadata: &Metadata) {
+    let fields = [
+        ("title", &metadata.title),
+        ("by", &metadata.author),
+        ("copyright", &metadata.copyright),
+        ("license", &metadata.license),
+        ("source", &metadata.source),
+        ("created", &metadata.created),
+        ("reveal", &metadata.reveal),
+        ("notes", &metadata.notes),
+    ];
+    for (key, value) in fields.iter() {
+        if let Some(value) = value {
+            writeln!(text, "{} {}", key, quote(value)).unwrap();
+        }
+    }
+    if !metadata.tags.is_empty() {
+        writeln!(text, "tags {}", quote(&join_tags(&metadata.tags))).unwrap();
+    }
+    if let Some(difficulty) = metadata.difficulty {
+        writeln!(text, "difficulty {}", difficulty).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{constraints, parse_non, write_non, Metadata, Puzzle, SimpleCell};
+
+    const DEMO: &str = r#"catalogue "picore tests"
+title "Smile"
+by "Alex"
+license CC-BY-4.0
+width 5
+height 5
+
+rows
+1,1
+1 1
+
+1,1
+3
+
+columns
+1
+2,1
+0
+2,1
+1
+
+goal 0101001010000001000101110
+"#;
+
+    #[test]
+    fn parse_works() {
+        let puzzle = parse_non(DEMO).unwrap();
+
+        assert_eq!(puzzle.metadata().title.as_deref(), Some("Smile"));
+        assert_eq!(puzzle.metadata().author.as_deref(), Some("Alex"));
+        assert_eq!(puzzle.metadata().license.as_deref(), Some("CC-BY-4.0"));
+        assert_eq!(
+            puzzle.row_constraints(),
+            &constraints![
+                [1, SimpleCell; 1, SimpleCell]
+                [1, SimpleCell; 1, SimpleCell]
+                []
+                [1, SimpleCell; 1, SimpleCell]
+                [3, SimpleCell]
+            ]
+        );
+        assert_eq!(puzzle.column_constraints()[2], vec![]);
+    }
+
+    #[test]
+    fn round_trips_metadata() {
+        let puzzle =
+            Puzzle::new(constraints![[1, SimpleCell] []], constraints![[1, SimpleCell]]).with_metadata(Metadata {
+                title: Some("A \"quoted\" title".to_owned()),
+                author: Some("Someone".to_owned()),
+                copyright: Some("(c) 2021".to_owned()),
+                license: Some("CC0".to_owned()),
+                source: Some("https://example.com/puzzle".to_owned()),
+                created: Some("2021-03-04".to_owned()),
+                tags: vec![
+                    "tiny".to_owned(),
+                    "test, with a comma".to_owned(),
+                    "back\\slash".to_owned(),
+                ],
+                difficulty: Some(3),
+                reveal: Some("Dot".to_owned()),
+                notes: Some("Two\nlines".to_owned()),
+            });
+
+        assert_eq!(parse_non(&write_non(&puzzle).unwrap()), Ok(puzzle));
+    }
+
+    #[test]
+    fn refuses_to_write_variants() {
+        let puzzle = Puzzle::new(constraints![[1, SimpleCell]], constraints![[1, SimpleCell]]);
+        assert!(write_non(&puzzle.clone().with_wrapping(true)).is_err());
+        assert!(write_non(&puzzle.with_givens(vec![(0, 0, Some(SimpleCell))])).is_err());
+    }
+
+    #[test]
+    fn reports_errors() {
+        let error = parse_non("width 2\nheight 1\nrows\n1\ncolumns\n1\nx\n").unwrap_err();
+        assert_eq!(error.line, 7);
+
+        let error = parse_non("rows\n").unwrap_err();
+        assert_eq!(error.line, 1);
+
+        let error = parse_non("title \"Hard\"\ndifficulty 4294967296\n").unwrap_err();
+        assert_eq!(error.line, 2);
+    }
+}
@@ -1,12 +1,52 @@
 use crate::cell::CellValue;
-use crate::{Board, Cell, ConstraintGroup, Puzzle};
+use crate::checker::{Checker, Lines, MAX_CROSSINGS};
+use crate::history::{Change, History};
+use crate::timer::Timer;
+use crate::{
+    Board, Cell, ClueKind, ConstraintGroup, DefaultScoring, Geometry, LineId, Metadata, Puzzle, Report, Scoring, Stats,
+};
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
 use bitflags::bitflags;
 use bitvec::prelude::*;
+use core::fmt;
+use core::time::Duration;
 
+/// Which lines are complete, by family and then by line.
 #[derive(Default)]
 struct Status {
-    row_status: BitVec,
-    column_status: BitVec,
+    families: Vec<BitVec>,
+    // The lines whose status changed since they were last auto-crossed, laid out like `families`.
+    changed: Vec<BitVec>,
+}
+
+/// The error returned when a move would change a locked cell.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LockedError {
+    /// The row of the locked cell.
+    pub row: usize,
+    /// The column of the locked cell.
+    pub column: usize,
+}
+
+impl fmt::Display for LockedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the cell at row {}, column {} is locked",
+            self.row + 1,
+            self.column + 1
+        )
+    }
+}
+
+impl core::error::Error for LockedError {}
+
+/// A snapshot of the board, which the player can return to.
+struct Checkpoint<C: CellValue> {
+    name: String,
+    board: Board<C>,
 }
 
 bitflags! {
@@ -20,28 +60,89 @@ bitflags! {
 pub struct Picross<C: CellValue> {
     puzzle: Puzzle<C>,
     board: Board<C>,
+    checker: Checker<C>,
     status: Status,
     options: Options,
+    history: History<C>,
+    // Oldest first.
+    checkpoints: Vec<Checkpoint<C>>,
+    timer: Timer,
+    stats: Stats,
+    scoring: Box<dyn Scoring>,
+    report: Option<Report>,
 }
 
 impl<C: CellValue> Picross<C> {
-    /// Creates a new Picross game for `puzzle`.
+    /// Creates a new Picross game for `puzzle`, with its givens placed and locked.
     pub fn new(puzzle: Puzzle<C>) -> Self {
+        let geometry = *puzzle.geometry();
         let mut picross = Picross {
             status: Status {
-                row_status: bitvec![0; puzzle.row_constraints().len()],
-                column_status: bitvec![0; puzzle.column_constraints().len()],
+                families: (0..geometry.families())
+                    .map(|family| bitvec![0; geometry.line_count(family)])
+                    .collect(),
+                changed: (0..geometry.families())
+                    .map(|family| bitvec![0; geometry.line_count(family)])
+                    .collect(),
             },
             options: Options::AUTO_CROSS_COMPLETED,
-            board: Board::new_empty(puzzle.row_constraints().len(), puzzle.column_constraints().len()),
+            board: Board::for_geometry(&geometry),
+            checker: Checker::new(&geometry),
             puzzle,
+            history: History::new(),
+            checkpoints: Vec::new(),
+            timer: Timer::start(),
+            stats: Stats::default(),
+            scoring: Box::new(DefaultScoring),
+            report: None,
         };
-        for r in 0..picross.height() {
-            picross.check_row(r);
+        for (row, column, given) in picross.puzzle.givens().to_vec() {
+            let cell = match given {
+                Some(value) => Cell::Filled(value),
+                None => Cell::CrossedOut,
+            };
+            picross.write(row, column, cell);
+            picross.lock_at(row, column);
+        }
+        for line in geometry.lines() {
+            picross.update_line_status(line);
+        }
+        for line in geometry.lines() {
+            picross.check_line(line);
+        }
+        picross.history.discard();
+        picross
+    }
+
+    /// Resumes a game of `puzzle` from a saved `board`, such as one stored in a [`Collection`](crate::Collection),
+    /// which has already been played for `elapsed`.
+    ///
+    /// # Panics
+    /// Panics if the board doesn't match the puzzle's dimensions.
+    pub fn with_board(puzzle: Puzzle<C>, board: Board<C>, elapsed: Duration) -> Self {
+        let mut picross = Picross::new(puzzle);
+        picross.timer = Timer::start_at(elapsed);
+        assert!(
+            board.width() == picross.width() && board.height() == picross.height(),
+            "a {}x{} board doesn't fit a {}x{} puzzle",
+            board.width(),
+            board.height(),
+            picross.width(),
+            picross.height()
+        );
+        for (row, column, &cell) in board.cells() {
+            if picross.puzzle.geometry().contains(row, column) && !picross.is_locked(row, column) {
+                picross.write(row, column, cell);
+                if board.is_locked(row, column) {
+                    picross.lock_at(row, column);
+                }
+            }
         }
-        for c in 0..picross.width() {
-            picross.check_column(c);
+        let geometry = *picross.puzzle.geometry();
+        for line in geometry.lines() {
+            picross.check_line(line);
         }
+        picross.history.discard();
         picross
     }
 
@@ -51,89 +152,460 @@ impl<C: CellValue> Picross<C> {
     }
 
     /// Crosses out the cell at `row` and `column`.
-    pub fn cross_out(&mut self, row: usize, column: usize) -> bool {
-        *self.board.get_mut(row, column) = Cell::CrossedOut;
-        self.check(row, column);
-        self.is_solved()
+    /// Returns whether or not the puzzle is solved afterwards, or an error if the cell is locked.
+    pub fn cross_out(&mut self, row: usize, column: usize) -> Result<bool, LockedError> {
+        self.move_at(row, column, Cell::CrossedOut)
     }
 
     /// Clears the cell at `row` and `column`.
-    pub fn clear_at(&mut self, row: usize, column: usize) -> bool {
-        *self.board.get_mut(row, column) = Cell::Empty;
-        self.check(row, column);
-        self.is_solved()
+    /// Returns whether or not the puzzle is solved afterwards, or an error if the cell is locked.
+    pub fn clear_at(&mut self, row: usize, column: usize) -> Result<bool, LockedError> {
+        self.move_at(row, column, Cell::Empty)
     }
 
     /// Places `value` into the cell at `row` and `column`.
+    /// Returns whether or not the puzzle is solved afterwards, or an error if the cell is locked.
+    pub fn place_at(&mut self, value: C, row: usize, column: usize) -> Result<bool, LockedError> {
+        self.move_at(row, column, Cell::Filled(value))
+    }
+
+    /// Sets every cell in the straight line from `start` to `end` to `cell`, as a single move.
+    /// Both ends are `(row, column)` pairs and are included in the line. Locked cells are skipped, as
+    /// in [`paint_rect`](Picross::paint_rect).
+    /// Returns whether or not the puzzle is solved afterwards.
+    ///
+    /// # Panics
+    /// Panics if `start` and `end` share neither a row nor a column.
+    pub fn paint_line(&mut self, cell: Cell<C>, start: (usize, usize), end: (usize, usize)) -> bool {
+        assert!(
+            start.0 == end.0 || start.1 == end.1,
+            "{:?} and {:?} are not in a straight line",
+            start,
+            end
+        );
+        self.paint_rect(cell, start, end)
+    }
+
+    /// Sets every cell in the rectangle with opposite corners `start` and `end` to `cell`, as a single move.
+    /// Both corners are `(row, column)` pairs and are included in the rectangle.
     /// Returns whether or not the puzzle is solved afterwards.
-    pub fn place_at(&mut self, value: C, row: usize, column: usize) -> bool {
-        *self.board.get_mut(row, column) = Cell::Filled(value);
+    ///
+    /// Locked cells are skipped rather than reported with a [`LockedError`], so a rectangle can be
+    /// painted across givens. The other cells still change.
+    pub fn paint_rect(&mut self, cell: Cell<C>, start: (usize, usize), end: (usize, usize)) -> bool {
+        let mut lines = self.no_lines();
+        for r in start.0.min(end.0)..=start.0.max(end.0) {
+            for c in start.1.min(end.1)..=start.1.max(end.1) {
+                self.set(r, c, cell);
+                self.mark_crossings(&mut lines, r, c);
+            }
+        }
+        self.check_lines(&lines);
+        self.finish_move()
+    }
+
+    /// Marks the cell at `row` and `column` as possibly filled.
+    /// Returns whether or not the puzzle is solved afterwards, or an error if the cell is locked.
+    pub fn mark_at(&mut self, row: usize, column: usize) -> Result<bool, LockedError> {
+        self.move_at(row, column, Cell::Marked)
+    }
+
+    /// Tentatively places `value` into the cell at `row` and `column`.
+    /// Tentative cells don't count towards solving the puzzle until they are committed.
+    /// Returns whether or not the puzzle is solved afterwards, or an error if the cell is locked.
+    pub fn place_tentative(&mut self, value: C, row: usize, column: usize) -> Result<bool, LockedError> {
+        self.move_at(row, column, Cell::Tentative(value))
+    }
+
+    /// Sets the cell at `row` and `column` to `cell`, as a single move.
+    fn move_at(&mut self, row: usize, column: usize, cell: Cell<C>) -> Result<bool, LockedError> {
+        if self.is_locked(row, column) {
+            return Err(LockedError { row, column });
+        }
+        self.set(row, column, cell);
         self.check(row, column);
-        self.is_solved()
+        Ok(self.finish_move())
     }
 
-    fn check_row(&mut self, row: usize) {
-        let completed = self.puzzle.row_is_solved(&self.board, row);
-        self.status.row_status.set(row, completed);
+    /// Locks the cell at `row` and `column` as it is, so moves can't change it. This is how givens
+    /// are placed, and can also keep cells revealed by a hint or checked as correct. Locking isn't a
+    /// move, and can't be undone. Undoing and redoing moves leaves the cell alone from then on, and
+    /// moves which only changed it are forgotten.
+    pub fn lock_at(&mut self, row: usize, column: usize) {
+        self.board.set_locked(row, column, true);
+        self.history.forget(row, column);
+    }
 
-        if self.options.contains(Options::AUTO_CROSS_COMPLETED) {
-            if completed {
-                for c in 0..self.width() {
-                    let cell = self.board.get_mut(row, c);
-                    if let Cell::Empty = *cell {
-                        *cell = Cell::CrossedOut;
-                    }
-                }
-            } else {
-                for c in 0..self.width() {
-                    if let Cell::CrossedOut = *self.board.get_mut(row, c) {
-                        if !self.puzzle.column_is_solved(&self.board, c) {
-                            *self.board.get_mut(row, c) = Cell::Empty
-                        }
-                    }
+    /// Fills every tentative cell with its value, as a single move.
+    /// Returns whether or not the puzzle is solved afterwards.
+    pub fn commit_tentative(&mut self) -> bool {
+        self.replace_tentative(Cell::Filled)
+    }
+
+    /// Clears every tentative cell, as a single move.
+    /// Returns whether or not the puzzle is solved afterwards.
+    pub fn discard_tentative(&mut self) -> bool {
+        self.replace_tentative(|_| Cell::Empty)
+    }
+
+    fn replace_tentative(&mut self, replacement: impl Fn(C) -> Cell<C>) -> bool {
+        let mut lines = self.no_lines();
+        for r in 0..self.height() {
+            for c in 0..self.width() {
+                if let Cell::Tentative(value) = *self.board.get(r, c) {
+                    self.set(r, c, replacement(value));
+                    self.mark_crossings(&mut lines, r, c);
                 }
             }
         }
+        self.check_lines(&lines);
+        self.finish_move()
+    }
+
+    /// Saves the board as a checkpoint called `name`, which can later be committed or rolled back.
+    /// Checkpoints nest: moves after this belong to its branch until a newer checkpoint is made.
+    pub fn checkpoint(&mut self, name: impl Into<String>) {
+        self.checkpoints.push(Checkpoint {
+            name: name.into(),
+            board: self.board.clone(),
+        });
+    }
+
+    /// Returns the names of the current checkpoints, oldest first.
+    pub fn checkpoints(&self) -> impl Iterator<Item = &str> {
+        self.checkpoints.iter().map(|checkpoint| checkpoint.name.as_str())
+    }
+
+    /// Keeps the moves made since the most recent checkpoint called `name`, and forgets it and any newer checkpoints.
+    /// Returns whether or not there was such a checkpoint.
+    pub fn commit_checkpoint(&mut self, name: &str) -> bool {
+        match self.find_checkpoint(name) {
+            Some(index) => {
+                self.checkpoints.truncate(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the board to the most recent checkpoint called `name`, and forgets it and any newer checkpoints.
+    /// The rollback is a single move which can be undone.
+    /// Returns whether or not there was such a checkpoint.
+    pub fn rollback(&mut self, name: &str) -> bool {
+        let index = match self.find_checkpoint(name) {
+            Some(index) => index,
+            None => return false,
+        };
+        let checkpoint = self.checkpoints.swap_remove(index);
+        self.checkpoints.truncate(index);
+
+        let mut lines = self.no_lines();
+        for (r, c, cell) in checkpoint.board.cells() {
+            if self.board.get(r, c) != cell {
+                self.set(r, c, *cell);
+                self.mark_crossings(&mut lines, r, c);
+            }
+        }
+        self.check_lines(&lines);
+        self.finish_move();
+        true
+    }
+
+    /// Returns the name of the checkpoint whose branch last changed the cell at `row` and `column`,
+    /// or `None` if it hasn't changed since the oldest checkpoint.
+    pub fn branch_at(&self, row: usize, column: usize) -> Option<&str> {
+        let cell = self.board.get(row, column);
+        self.checkpoints
+            .iter()
+            .rev()
+            .find(|checkpoint| checkpoint.board.get(row, column) != cell)
+            .map(|checkpoint| checkpoint.name.as_str())
+    }
+
+    fn find_checkpoint(&self, name: &str) -> Option<usize> {
+        self.checkpoints.iter().rposition(|checkpoint| checkpoint.name == name)
+    }
+
+    /// Undoes the most recent move. Returns whether or not there was a move to undo.
+    pub fn undo(&mut self) -> bool {
+        let len = match self.history.undo() {
+            Some(len) => len,
+            None => return false,
+        };
+        for index in (0..len).rev() {
+            let change = self.history.undone(index);
+            self.write(change.row, change.column, change.before);
+        }
+        true
+    }
+
+    /// Redoes the most recently undone move. Returns whether or not there was a move to redo.
+    pub fn redo(&mut self) -> bool {
+        let len = match self.history.redo() {
+            Some(len) => len,
+            None => return false,
+        };
+        for index in 0..len {
+            let change = self.history.redone(index);
+            self.write(change.row, change.column, change.after);
+        }
+        true
     }
 
-    fn check_column(&mut self, column: usize) {
-        let completed = self.puzzle.column_is_solved(&self.board, column);
-        self.status.column_status.set(column, completed);
+    /// Whether or not there is a move to undo.
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
 
-        if self.options.contains(Options::AUTO_CROSS_COMPLETED) {
-            if completed {
-                for r in 0..self.height() {
-                    let cell = self.board.get_mut(r, column);
-                    if let Cell::Empty = *cell {
-                        *cell = Cell::CrossedOut;
-                    }
+    /// Whether or not there is a move to redo.
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Sets the cell at `row` and `column`, recording the change in the history.
+    /// Does nothing if the position isn't a cell of the puzzle's geometry, or is locked.
+    fn set(&mut self, row: usize, column: usize, cell: Cell<C>) {
+        if !self.puzzle.geometry().contains(row, column) || self.is_locked(row, column) {
+            return;
+        }
+        let before = self.write(row, column, cell);
+        if before != cell {
+            self.history.record(Change {
+                row,
+                column,
+                before,
+                after: cell,
+            });
+        }
+    }
+
+    /// Counts a move if it changed anything, and produces the report if it solved the puzzle.
+    /// Returns whether or not the puzzle is solved.
+    fn finish_move(&mut self) -> bool {
+        if self.history.commit() {
+            self.stats.moves += 1;
+        }
+
+        let solved = self.is_solved();
+        if solved && self.report.is_none() {
+            self.timer.pause();
+            let stats = self.stats();
+            self.report = Some(Report {
+                score: self.scoring.score(&stats),
+                stats,
+            });
+        }
+        solved
+    }
+
+    /// Sets the cell at `row` and `column` and updates the status of the lines through it.
+    /// Returns the previous cell.
+    fn write(&mut self, row: usize, column: usize, cell: Cell<C>) -> Cell<C> {
+        let before = core::mem::replace(self.board.get_mut(row, column), cell);
+        if before != cell {
+            self.checker.update(row, column, &before, &cell);
+            for line in self.checker.crossings(row, column) {
+                self.update_line_status(line);
+            }
+        }
+        before
+    }
+
+    fn update_line_status(&mut self, line: LineId) {
+        if !self.puzzle.is_clue_known(line) {
+            // Anything goes in a line whose clue is unknown.
+            self.set_completed(line, true);
+            return;
+        }
+        match self.puzzle.mega_band(line) {
+            // Both lines of a band of mega clues are complete once the whole band is.
+            Some(first) => {
+                let completed = self.puzzle.line_is_solved(&self.board, first);
+                self.set_completed(first, completed);
+                self.set_completed(LineId::new(first.family, first.index + 1), completed);
+            }
+            // The checker only compares runs with the blocks, and can't tell when a block wraps around.
+            None if self.puzzle.wraps() || self.puzzle.clue_kind(line) != ClueKind::Blocks => {
+                let completed = self.puzzle.line_is_solved(&self.board, line);
+                self.set_completed(line, completed);
+            }
+            None => {
+                let completed = self.checker.line(line).satisfies(self.puzzle.constraint(line));
+                self.set_completed(line, completed);
+            }
+        }
+    }
+
+    fn set_completed(&mut self, line: LineId, completed: bool) {
+        if self.status.families[line.family][line.index] != completed {
+            self.status.families[line.family].set(line.index, completed);
+            self.status.changed[line.family].set(line.index, true);
+        }
+    }
+
+    /// Auto-crosses every cell of `line`, if enabled.
+    fn check_line(&mut self, line: LineId) {
+        self.status.changed[line.family].set(line.index, false);
+        for i in 0..self.checker.cells(line).len() {
+            let (r, c) = self.checker.cells(line)[i];
+            self.check_cell(line, r, c);
+        }
+    }
+
+    /// Auto-crosses the cell at `row` and `column` of `line`, if enabled: it is crossed out if empty
+    /// and the line is complete, and cleared if crossed out and the line isn't. Lines with unknown
+    /// clues are never auto-crossed.
+    fn check_cell(&mut self, line: LineId, row: usize, column: usize) {
+        if !self.options.contains(Options::AUTO_CROSS_COMPLETED) || !self.puzzle.is_clue_known(line) {
+            return;
+        }
+        match *self.board.get(row, column) {
+            Cell::Empty if self.status.families[line.family][line.index] => self.set(row, column, Cell::CrossedOut),
+            Cell::CrossedOut if !self.status.families[line.family][line.index] => {
+                // Leave cells crossed by another completed line through them.
+                let crossed = self.checker.crossings(row, column).any(|other| {
+                    other != line && self.puzzle.is_clue_known(other) && self.status.families[other.family][other.index]
+                });
+                if !crossed {
+                    self.set(row, column, Cell::Empty);
                 }
-            } else {
-                for r in 0..self.height() {
-                    if let Cell::CrossedOut = *self.board.get_mut(r, column) {
-                        if !self.puzzle.row_is_solved(&self.board, r) {
-                            *self.board.get_mut(r, column) = Cell::Empty;
-                        }
-                    }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns a set of lines with none of them marked.
+    fn no_lines(&self) -> Vec<BitVec> {
+        self.status
+            .families
+            .iter()
+            .map(|family| bitvec![0; family.len()])
+            .collect()
+    }
+
+    /// Marks the lines through the cell at `row` and `column` in `lines`.
+    fn mark_crossings(&self, lines: &mut [BitVec], row: usize, column: usize) {
+        for line in self.affected_lines(row, column) {
+            lines[line.family].set(line.index, true);
+        }
+    }
+
+    /// Applies auto-crossing to the marked `lines`.
+    fn check_lines(&mut self, lines: &[BitVec]) {
+        for (family, marked) in lines.iter().enumerate() {
+            for index in marked.iter_ones() {
+                self.check_line(LineId::new(family, index));
+            }
+        }
+    }
+
+    /// Returns the lines whose status depends on the cell at `row` and `column`: the lines through
+    /// it, and the other line of any band of mega clues they are in.
+    fn affected_lines(&self, row: usize, column: usize) -> Lines<{ 2 * MAX_CROSSINGS }> {
+        let mut lines = Lines::new();
+        for line in self.checker.crossings(row, column) {
+            match self.puzzle.mega_band(line) {
+                Some(first) => {
+                    lines.push(first);
+                    lines.push(LineId::new(first.family, first.index + 1));
                 }
+                None => lines.push(line),
             }
         }
+        lines
     }
 
-    /// Checks whether or not the given row and column are solved and stores the result.
+    /// Applies auto-crossing after the cell at `row` and `column` changed. The rest of a line only
+    /// needs checking if its status changed, as auto-crossing already agrees with the old status.
     fn check(&mut self, row: usize, column: usize) {
-        self.check_row(row);
-        self.check_column(column);
+        for line in self.affected_lines(row, column) {
+            if self.status.changed[line.family][line.index] {
+                self.check_line(line);
+            } else if self.checker.crossings(row, column).any(|crossing| crossing == line) {
+                self.check_cell(line, row, column);
+            }
+        }
+    }
+
+    /// Pauses the game timer.
+    pub fn pause(&mut self) {
+        self.timer.pause();
+    }
+
+    /// Resumes the game timer. Does nothing once the game has been solved.
+    pub fn resume(&mut self) {
+        if self.report.is_none() {
+            self.timer.resume();
+        }
+    }
+
+    /// Whether or not the game timer is paused.
+    pub fn is_paused(&self) -> bool {
+        self.timer.is_paused()
+    }
+
+    /// The time spent playing, excluding pauses. Always zero without the `std` feature.
+    pub fn elapsed(&self) -> Duration {
+        self.timer.elapsed()
+    }
+
+    /// Records that the player used a hint.
+    pub fn record_hint(&mut self) {
+        self.stats.hints += 1;
+    }
+
+    /// Records that the player made a mistake.
+    pub fn record_mistake(&mut self) {
+        self.stats.mistakes += 1;
+    }
+
+    /// Returns the current stats of this game.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            elapsed: self.elapsed(),
+            ..self.stats
+        }
     }
 
-    /// Returns the status of the puzzle's rows and columns.
+    /// Sets the model used to score this game once it is solved.
+    pub fn set_scoring(&mut self, scoring: impl Scoring + 'static) {
+        self.scoring = Box::new(scoring);
+    }
+
+    /// Returns the report made when this game was first solved, if it has been.
+    pub fn report(&self) -> Option<&Report> {
+        self.report.as_ref()
+    }
+
+    /// Returns the status of the puzzle's rows and columns, which are the first two families of lines.
     pub fn status(&self) -> (&BitVec, &BitVec) {
-        (&self.status.row_status, &self.status.column_status)
+        (&self.status.families[0], &self.status.families[1])
     }
 
-    /// Checks whether or not the puzzle is solved.  all the row and column constraints are satisfied.
+    /// Returns which lines of `family` are complete, indexed like the puzzle's constraint groups.
+    pub fn line_status(&self, family: usize) -> &BitVec {
+        &self.status.families[family]
+    }
+
+    /// Whether or not `line` satisfies its constraint.
+    pub fn is_line_solved(&self, line: LineId) -> bool {
+        self.status.families[line.family][line.index]
+    }
+
+    /// Checks whether or not the puzzle is solved.  all the constraints of every line are satisfied.
     pub fn is_solved(&self) -> bool {
-        self.status.row_status.all() && self.status.column_status.all()
+        self.status.families.iter().all(|family| family.all())
+    }
+
+    /// Returns the board, for example to save an unfinished game.
+    pub fn board(&self) -> &Board<C> {
+        &self.board
+    }
+
+    /// Whether or not the cell at `row` and `column` is locked, so moves leave it unchanged.
+    /// Givens are locked.
+    pub fn is_locked(&self, row: usize, column: usize) -> bool {
+        self.board.is_locked(row, column)
     }
 
     /// Gets the cell at `row` and `column`.
@@ -141,14 +613,24 @@ impl<C: CellValue> Picross<C> {
         self.board.get(row, column)
     }
 
+    /// Returns the puzzle's metadata.
+    pub fn metadata(&self) -> &Metadata {
+        self.puzzle.metadata()
+    }
+
     /// Returns the row constraint group.
     pub fn row_constraints(&self) -> &ConstraintGroup<C> {
-        &self.puzzle.row_constraints()
+        self.puzzle.row_constraints()
     }
 
     /// Returns the column constraint group.
     pub fn column_constraints(&self) -> &ConstraintGroup<C> {
-        &self.puzzle.column_constraints()
+        self.puzzle.column_constraints()
+    }
+
+    /// Returns the shape of the puzzle's cells and lines.
+    pub fn geometry(&self) -> &Geometry {
+        self.puzzle.geometry()
     }
 
     /// The width of this board.
@@ -161,3 +643,295 @@ impl<C: CellValue> Picross<C> {
         self.board.height()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{constraints, Board, Cell, ConstraintEntry, Geometry, LineId, LockedError, Picross, Puzzle};
+    use bitvec::prelude::*;
+    use std::time::Duration;
+
+    #[derive(PartialEq, Copy, Clone, Debug)]
+    struct SimpleCell;
+
+    fn test_puzzle() -> Puzzle<SimpleCell> {
+        // #.
+        // ##
+        Puzzle::new(
+            constraints![
+                [1, SimpleCell]
+                [2, SimpleCell]
+            ],
+            constraints![
+                [2, SimpleCell]
+                [1, SimpleCell]
+            ],
+        )
+    }
+
+    #[test]
+    fn report_on_solve() {
+        let mut picross = Picross::new(test_puzzle());
+        picross.record_hint();
+
+        assert!(!picross.place_at(SimpleCell, 0, 0).unwrap());
+        // Already crossed out automatically, so this doesn't count as a move.
+        assert!(!picross.cross_out(0, 1).unwrap());
+        assert!(!picross.place_at(SimpleCell, 1, 0).unwrap());
+        assert!(picross.report().is_none());
+        assert!(picross.place_at(SimpleCell, 1, 1).unwrap());
+
+        let report = picross.report().expect("solved game has a report");
+        assert_eq!(report.stats.moves, 3);
+        assert_eq!(report.stats.hints, 1);
+        assert_eq!(report.stats.mistakes, 0);
+        assert!(picross.is_paused());
+    }
+
+    #[test]
+    fn paused_time_is_not_counted() {
+        let mut picross = Picross::new(test_puzzle());
+        picross.pause();
+
+        let elapsed = picross.elapsed();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(picross.elapsed(), elapsed);
+    }
+
+    #[test]
+    fn paint_rect_is_one_move() {
+        let mut picross = Picross::new(test_puzzle());
+
+        assert!(!picross.paint_rect(Cell::Filled(SimpleCell), (0, 0), (1, 1)));
+        assert_eq!(picross.stats().moves, 1);
+        assert!(!picross.paint_rect(Cell::Filled(SimpleCell), (0, 0), (1, 1)));
+        assert_eq!(picross.stats().moves, 1);
+        assert!(picross.cells().all(|(_, _, cell)| *cell == Cell::Filled(SimpleCell)));
+
+        assert!(picross.undo());
+        assert!(picross.cells().all(|(_, _, cell)| *cell == Cell::Empty));
+        assert!(!picross.undo());
+    }
+
+    #[test]
+    fn paint_line_solves() {
+        let mut picross = Picross::new(test_puzzle());
+
+        assert!(!picross.paint_line(Cell::Filled(SimpleCell), (1, 1), (1, 0)));
+        assert!(picross.place_at(SimpleCell, 0, 0).unwrap());
+        assert_eq!(*picross.get(0, 1), Cell::CrossedOut);
+    }
+
+    #[test]
+    #[should_panic]
+    fn paint_line_rejects_diagonals() {
+        let mut picross = Picross::new(test_puzzle());
+        picross.paint_line(Cell::CrossedOut, (0, 0), (1, 1));
+    }
+
+    #[test]
+    fn undo_restores_auto_crosses() {
+        let mut picross = Picross::new(test_puzzle());
+        picross.place_at(SimpleCell, 0, 0).unwrap();
+        picross.place_at(SimpleCell, 1, 0).unwrap();
+        picross.place_at(SimpleCell, 1, 1).unwrap();
+        assert!(picross.is_solved());
+        assert_eq!(*picross.get(0, 1), Cell::CrossedOut);
+
+        assert!(picross.undo());
+        assert!(!picross.is_solved());
+        assert_eq!(*picross.get(1, 1), Cell::Empty);
+        assert!(picross.undo());
+        assert!(picross.undo());
+        assert!(picross.cells().all(|(_, _, cell)| *cell == Cell::Empty));
+
+        while picross.redo() {}
+        assert!(picross.is_solved());
+        assert_eq!(*picross.get(0, 1), Cell::CrossedOut);
+    }
+
+    #[test]
+    fn auto_crosses_moves_in_unchanged_lines() {
+        let mut picross = Picross::new(test_puzzle());
+        picross.place_at(SimpleCell, 0, 0).unwrap();
+        assert_eq!(*picross.get(0, 1), Cell::CrossedOut);
+
+        // The first row stays complete, so the cell is crossed out again.
+        picross.clear_at(0, 1).unwrap();
+        assert_eq!(*picross.get(0, 1), Cell::CrossedOut);
+
+        // Neither line through the cell is complete.
+        picross.cross_out(1, 1).unwrap();
+        assert_eq!(*picross.get(1, 1), Cell::Empty);
+    }
+
+    #[test]
+    fn tentative_cells_do_not_solve() {
+        let mut picross = Picross::new(test_puzzle());
+        picross.place_at(SimpleCell, 0, 0).unwrap();
+        picross.mark_at(1, 0).unwrap();
+        assert!(!picross.place_tentative(SimpleCell, 1, 0).unwrap());
+        assert!(!picross.place_tentative(SimpleCell, 1, 1).unwrap());
+
+        assert!(!picross.discard_tentative());
+        assert_eq!(*picross.get(1, 0), Cell::Empty);
+
+        picross.place_tentative(SimpleCell, 1, 0).unwrap();
+        picross.place_tentative(SimpleCell, 1, 1).unwrap();
+        assert!(picross.commit_tentative());
+        assert_eq!(*picross.get(1, 1), Cell::Filled(SimpleCell));
+
+        assert!(picross.undo());
+        assert_eq!(*picross.get(1, 1), Cell::Tentative(SimpleCell));
+    }
+
+    #[test]
+    fn rollback_restores_checkpoint() {
+        let mut picross = Picross::new(test_puzzle());
+        picross.place_at(SimpleCell, 0, 0).unwrap();
+        picross.checkpoint("guess");
+        picross.place_at(SimpleCell, 0, 1).unwrap();
+        picross.checkpoint("nested");
+        picross.place_at(SimpleCell, 1, 0).unwrap();
+
+        assert_eq!(picross.branch_at(0, 0), None);
+        assert_eq!(picross.branch_at(0, 1), Some("guess"));
+        assert_eq!(picross.branch_at(1, 0), Some("nested"));
+        assert_eq!(picross.checkpoints().collect::<Vec<_>>(), ["guess", "nested"]);
+
+        assert!(picross.rollback("guess"));
+        assert!(!picross.rollback("nested"));
+        assert_eq!(picross.checkpoints().count(), 0);
+        assert_eq!(*picross.get(0, 1), Cell::CrossedOut);
+        assert_eq!(*picross.get(1, 0), Cell::Empty);
+        assert!(picross.is_line_solved(LineId::new(0, 0)));
+        assert!(!picross.is_line_solved(LineId::new(1, 0)));
+        assert_eq!(picross.stats().moves, 4);
+
+        assert!(picross.undo());
+        assert_eq!(*picross.get(0, 1), Cell::Filled(SimpleCell));
+    }
+
+    #[test]
+    fn commit_keeps_branch() {
+        let mut picross = Picross::new(test_puzzle());
+        picross.checkpoint("guess");
+        picross.place_at(SimpleCell, 1, 0).unwrap();
+
+        assert!(picross.commit_checkpoint("guess"));
+        assert!(!picross.commit_checkpoint("guess"));
+        assert_eq!(picross.branch_at(1, 0), None);
+        assert_eq!(*picross.get(1, 0), Cell::Filled(SimpleCell));
+    }
+
+    #[test]
+    fn resumes_saved_board() {
+        let mut picross = Picross::new(test_puzzle());
+        picross.place_at(SimpleCell, 0, 0).unwrap();
+        picross.place_at(SimpleCell, 1, 0).unwrap();
+
+        let mut resumed = Picross::with_board(test_puzzle(), picross.board().clone(), Duration::from_secs(60));
+        assert!(resumed.elapsed() >= Duration::from_secs(60));
+        assert_eq!(resumed.board(), picross.board());
+        assert_eq!(resumed.status(), picross.status());
+        assert!(!resumed.can_undo());
+        assert!(resumed.place_at(SimpleCell, 1, 1).unwrap());
+
+        // Saved boards are auto-crossed like any other.
+        let mut board = Board::new_empty(2, 2);
+        *board.get_mut(0, 0) = Cell::Filled(SimpleCell);
+        let resumed = Picross::with_board(test_puzzle(), board, Duration::ZERO);
+        assert_eq!(*resumed.get(0, 1), Cell::CrossedOut);
+        assert!(!resumed.can_undo());
+    }
+
+    #[test]
+    fn givens_are_locked() {
+        let puzzle = Puzzle::new(
+            constraints![[1, SimpleCell][1, SimpleCell]],
+            constraints![[1, SimpleCell][1, SimpleCell]],
+        )
+        .with_givens(vec![(0, 0, Some(SimpleCell))]);
+        let mut picross = Picross::new(puzzle);
+        assert_eq!(*picross.get(0, 0), Cell::Filled(SimpleCell));
+        assert!(picross.is_locked(0, 0));
+        assert!(!picross.can_undo());
+
+        assert_eq!(picross.cross_out(0, 0), Err(LockedError { row: 0, column: 0 }));
+        assert_eq!(*picross.get(0, 0), Cell::Filled(SimpleCell));
+        assert!(picross.place_at(SimpleCell, 1, 1).unwrap());
+    }
+
+    #[test]
+    fn locked_cells_cannot_change() {
+        let mut picross = Picross::new(test_puzzle());
+        picross.place_at(SimpleCell, 0, 0).unwrap();
+        picross.lock_at(0, 0);
+        assert_eq!(picross.clear_at(0, 0), Err(LockedError { row: 0, column: 0 }));
+
+        assert!(picross.undo());
+        assert_eq!(*picross.get(0, 0), Cell::Filled(SimpleCell));
+        picross.paint_rect(Cell::CrossedOut, (0, 0), (1, 1));
+        assert_eq!(*picross.get(0, 0), Cell::Filled(SimpleCell));
+
+        let resumed = Picross::with_board(test_puzzle(), picross.board().clone(), picross.elapsed());
+        assert!(resumed.is_locked(0, 0));
+    }
+
+    #[test]
+    fn locking_forgets_moves() {
+        let mut picross = Picross::new(test_puzzle());
+        picross.mark_at(1, 0).unwrap();
+        picross.lock_at(1, 0);
+        assert!(!picross.undo());
+        assert!(!picross.can_undo());
+        assert_eq!(*picross.get(1, 0), Cell::Marked);
+
+        picross.mark_at(1, 1).unwrap();
+        assert!(picross.undo());
+        picross.lock_at(1, 1);
+        assert!(!picross.redo());
+        assert_eq!(*picross.get(1, 1), Cell::Empty);
+    }
+
+    #[test]
+    fn hexagon_auto_crosses_each_axis() {
+        //  . #
+        // . # .
+        //  . .
+        let one = vec![ConstraintEntry {
+            value: SimpleCell,
+            size: 1,
+        }];
+        let puzzle = Puzzle::from_geometry(
+            Geometry::Hexagon { radius: 1 },
+            vec![
+                vec![one.clone(), one.clone(), vec![]],
+                vec![vec![], one.clone(), one],
+                vec![
+                    vec![],
+                    vec![ConstraintEntry {
+                        value: SimpleCell,
+                        size: 2,
+                    }],
+                    vec![],
+                ],
+            ],
+        );
+        let mut picross = Picross::new(puzzle);
+        assert_eq!(picross.line_status(2), &bitvec![1, 0, 1]);
+        assert_eq!(*picross.get(2, 0), Cell::CrossedOut);
+        assert_eq!(*picross.get(0, 1), Cell::CrossedOut);
+        assert_eq!(*picross.get(0, 0), Cell::Empty);
+
+        assert!(!picross.place_at(SimpleCell, 1, 1).unwrap());
+        assert!(picross.is_line_solved(LineId::new(0, 1)));
+        assert!(!picross.is_line_solved(LineId::new(2, 1)));
+        assert_eq!(*picross.get(1, 2), Cell::CrossedOut);
+
+        // The top left corner isn't a cell, so painting over it does nothing there.
+        assert!(!picross.paint_rect(Cell::Filled(SimpleCell), (0, 0), (0, 0)));
+        assert_eq!(*picross.get(0, 0), Cell::Empty);
+        assert!(picross.place_at(SimpleCell, 0, 2).unwrap());
+        assert_eq!(picross.line_status(2), &bitvec![1, 1, 1]);
+    }
+}
@@ -1,13 +1,17 @@
 use crate::cell::CellValue;
-use crate::{Board, Cell};
+use crate::mega::band_is_solved;
+use crate::{Cell, Geometry, Grid, LineId, MegaClue, Metadata, Transform};
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
 use itertools::Itertools;
 
 /// Simple syntax for creating an entire constraint group.
 #[macro_export]
 macro_rules! constraints {
     ($([$($size:expr, $value:expr $(,)?);* $(;)?])*) => {
-        vec![$(
-            vec![$(
+        $crate::__private::vec![$(
+            $crate::__private::vec![$(
                 $crate::ConstraintEntry { value: $value, size: $size }
             ),*]
         ),*]
@@ -15,6 +19,7 @@ macro_rules! constraints {
 }
 
 /// An entry in a constraint.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ConstraintEntry<C> {
     /// The cell this entry expects.
     pub value: C,
@@ -30,50 +35,284 @@ impl<C> From<(usize, C)> for ConstraintEntry<C> {
 
 /// A constraint. Fully describes a row, column, etc.
 pub type Constraint<C> = Vec<ConstraintEntry<C>>;
+
+/// How much of a line's constraint its clue shows, which decides how the line is checked.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ClueKind {
+    /// The usual clue, listing every block in order.
+    Blocks,
+    /// Only the total number of filled cells, of any value.
+    Total,
+    /// Only the number of blocks.
+    Count,
+}
+
 /// A group of constraints. Fully describes all rows, all columns, etc.
 pub type ConstraintGroup<C> = Vec<Constraint<C>>;
 
 /// A picross puzzle.
+#[derive(Clone, Debug, PartialEq)]
 pub struct Puzzle<C> {
-    row_constraints: ConstraintGroup<C>,
-    column_constraints: ConstraintGroup<C>,
+    geometry: Geometry,
+    // One group for each family of lines in the geometry.
+    groups: Vec<ConstraintGroup<C>>,
+    // Ordered within each band.
+    mega: Vec<MegaClue<C>>,
+    wraps: bool,
+    // Sorted, without duplicates.
+    unknown: Vec<LineId>,
+    givens: Vec<(usize, usize, Option<C>)>,
+    // Sorted by line, only for lines which aren't `ClueKind::Blocks`.
+    kinds: Vec<(LineId, ClueKind)>,
+    metadata: Metadata,
 }
 
 impl<C> Puzzle<C> {
-    /// Creates a new puzzle with the given constraint groups.
+    /// Creates a new puzzle on a square grid with the given constraint groups.
     pub fn new(row_constraints: ConstraintGroup<C>, column_constraints: ConstraintGroup<C>) -> Self {
         Self {
-            row_constraints,
-            column_constraints,
+            geometry: Geometry::Square {
+                width: column_constraints.len(),
+                height: row_constraints.len(),
+            },
+            groups: vec![row_constraints, column_constraints],
+            mega: Vec::new(),
+            wraps: false,
+            unknown: Vec::new(),
+            givens: Vec::new(),
+            kinds: Vec::new(),
+            metadata: Metadata::default(),
+        }
+    }
+
+    /// Creates a new puzzle laid out in `geometry`, with a constraint group for each of its
+    /// families of lines.
+    ///
+    /// # Panics
+    /// Panics if the groups don't match the geometry's lines.
+    pub fn from_geometry(geometry: Geometry, groups: Vec<ConstraintGroup<C>>) -> Self {
+        assert_eq!(
+            groups.len(),
+            geometry.families(),
+            "expected a constraint group per line family"
+        );
+        for (family, group) in groups.iter().enumerate() {
+            assert_eq!(
+                group.len(),
+                geometry.line_count(family),
+                "expected a constraint for every {}",
+                geometry.family_name(family)
+            );
+        }
+        Self {
+            geometry,
+            groups,
+            mega: Vec::new(),
+            wraps: false,
+            unknown: Vec::new(),
+            givens: Vec::new(),
+            kinds: Vec::new(),
+            metadata: Metadata::default(),
+        }
+    }
+
+    /// Adds mega clues to this puzzle, replacing any it had. The clues of each band are given in
+    /// order, and the rest of the band's cells are described by its lines' own constraints.
+    ///
+    /// # Panics
+    /// Panics if the puzzle isn't on a square grid, if a clue's band runs off the grid, or if two
+    /// bands overlap.
+    pub fn with_mega_clues(self, mega: Vec<MegaClue<C>>) -> Self {
+        assert!(
+            mega.is_empty() || matches!(self.geometry, Geometry::Square { .. }),
+            "only square puzzles can have mega clues"
+        );
+        for clue in &mega {
+            assert!(
+                clue.line.index + 1 < self.geometry.line_count(clue.line.family),
+                "a mega clue on the last {} has no second line",
+                self.geometry.family_name(clue.line.family)
+            );
+            assert!(
+                mega.iter().all(|other| other.line.family != clue.line.family
+                    || other.line.index == clue.line.index
+                    || other.line.index.abs_diff(clue.line.index) > 1),
+                "mega clue bands can't overlap"
+            );
+        }
+        Self { mega, ..self }
+    }
+
+    /// Returns the mega clues, which describe shapes spanning two lines.
+    pub fn mega_clues(&self) -> &[MegaClue<C>] {
+        &self.mega
+    }
+
+    /// Returns the first line of the band of mega clues `line` belongs to, if any.
+    pub(crate) fn mega_band(&self, line: LineId) -> Option<LineId> {
+        self.mega
+            .iter()
+            .map(|clue| clue.line)
+            .find(|first| first.family == line.family && (first.index == line.index || first.index + 1 == line.index))
+    }
+
+    /// Sets whether or not every line wraps around, so a block may continue from the last cell of
+    /// a line to the first. Each line's blocks are listed from the first one starting in the line,
+    /// so a block which wraps around is always the last. Lines in bands of mega clues don't wrap.
+    pub fn with_wrapping(self, wraps: bool) -> Self {
+        Self { wraps, ..self }
+    }
+
+    /// Whether or not this puzzle's lines wrap around.
+    pub fn wraps(&self) -> bool {
+        self.wraps
+    }
+
+    /// Hides the clues of `lines`, so any arrangement of cells is allowed in them. Their constraints
+    /// are kept, but ignored.
+    pub fn with_unknown_clues(self, lines: impl IntoIterator<Item = LineId>) -> Self {
+        let mut unknown = lines.into_iter().collect::<Vec<_>>();
+        unknown.sort_unstable();
+        unknown.dedup();
+        Self { unknown, ..self }
+    }
+
+    /// Whether or not the clue of `line` is known.
+    pub fn is_clue_known(&self, line: LineId) -> bool {
+        self.unknown.binary_search(&line).is_err()
+    }
+
+    /// Returns the lines whose clues are unknown, in order.
+    pub fn unknown_clues(&self) -> &[LineId] {
+        &self.unknown
+    }
+
+    /// Reveals cells on the starting board, which every solution must agree with. Each given is
+    /// the row and column of a cell, and `Some(value)` if it is filled or `None` if it is empty.
+    /// Repeated givens are only kept once.
+    ///
+    /// # Panics
+    /// Panics if a given isn't at a cell of the puzzle's geometry, or two givens at the same cell disagree.
+    pub fn with_givens(self, mut givens: Vec<(usize, usize, Option<C>)>) -> Self
+    where
+        C: PartialEq,
+    {
+        for &(row, col, _) in &givens {
+            assert!(
+                self.geometry.contains(row, col),
+                "there is no cell at row {}, column {} for a given",
+                row,
+                col
+            );
+        }
+        givens.sort_by_key(|&(row, col, _)| (row, col));
+        givens.dedup_by(|&mut (row, col, ref given), &mut (first_row, first_col, ref first)| {
+            let same_cell = (row, col) == (first_row, first_col);
+            assert!(
+                !same_cell || given == first,
+                "the givens at row {}, column {} disagree",
+                row,
+                col
+            );
+            same_cell
+        });
+        Self { givens, ..self }
+    }
+
+    /// Returns the cells revealed on the starting board, in order of row and then column.
+    pub fn givens(&self) -> &[(usize, usize, Option<C>)] {
+        &self.givens
+    }
+
+    /// Sets how the clues of some lines are read. A line with a [`Total`](ClueKind::Total) or
+    /// [`Count`](ClueKind::Count) clue keeps its full constraint, but only the total size or the
+    /// number of its blocks has to match. Other lines, and lines in bands of mega clues, keep
+    /// [`Blocks`](ClueKind::Blocks) clues.
+    pub fn with_clue_kinds(self, kinds: impl IntoIterator<Item = (LineId, ClueKind)>) -> Self {
+        let kinds = kinds
+            .into_iter()
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .filter(|&(_, kind)| kind != ClueKind::Blocks)
+            .collect();
+        Self { kinds, ..self }
+    }
+
+    /// Returns how the clue of `line` is read.
+    pub fn clue_kind(&self, line: LineId) -> ClueKind {
+        match self.kinds.binary_search_by_key(&line, |&(line, _)| line) {
+            Ok(index) => self.kinds[index].1,
+            Err(_) => ClueKind::Blocks,
         }
     }
 
+    /// Returns the lines whose clues aren't [`Blocks`](ClueKind::Blocks) clues, in order, with
+    /// their kinds.
+    pub fn clue_kinds(&self) -> &[(LineId, ClueKind)] {
+        &self.kinds
+    }
+
+    /// Sets this puzzle's metadata.
+    pub fn with_metadata(self, metadata: Metadata) -> Self {
+        Self { metadata, ..self }
+    }
+
+    /// Returns this puzzle's metadata.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Returns a mutable reference to this puzzle's metadata.
+    pub fn metadata_mut(&mut self) -> &mut Metadata {
+        &mut self.metadata
+    }
+
+    /// Returns the shape of this puzzle's cells.
+    pub fn geometry(&self) -> &Geometry {
+        &self.geometry
+    }
+
+    /// Returns the constraint groups, one for each family of lines.
+    pub fn constraint_groups(&self) -> &[ConstraintGroup<C>] {
+        &self.groups
+    }
+
+    /// Returns the constraint for `line`.
+    pub fn constraint(&self, line: LineId) -> &Constraint<C> {
+        &self.groups[line.family][line.index]
+    }
+
     /// Returns the row constraint group.
     pub fn row_constraints(&self) -> &ConstraintGroup<C> {
-        &self.row_constraints
+        &self.groups[0]
     }
 
-    /// Returns the column constraint group.
+    /// Returns the column constraint group. For geometries other than squares, this is the
+    /// second family of lines.
     pub fn column_constraints(&self) -> &ConstraintGroup<C> {
-        &self.column_constraints
+        &self.groups[1]
     }
 }
 
 impl<C: CellValue> Puzzle<C> {
-    fn is_solved<'a, I: IntoIterator<Item = &'a Cell<C>>>(constraint: &'a Constraint<C>, cells: I) -> bool {
+    pub(crate) fn is_solved<I: IntoIterator<Item = Cell<C>>>(
+        kind: ClueKind,
+        constraint: &Constraint<C>,
+        cells: I,
+    ) -> bool {
         let mut groups = cells.into_iter().peekable().batching(|it| {
             let value = loop {
                 match it.next() {
                     None => return None, // out of cells
-                    Some(Cell::Empty) | Some(Cell::CrossedOut) => continue,
                     Some(Cell::Filled(value)) => break value,
+                    Some(_) => continue,
                 }
             };
             let mut size = 1;
 
             loop {
                 match it.peek() {
-                    Some(Cell::Filled(next)) if *next == *value => {
+                    Some(Cell::Filled(next)) if *next == value => {
                         it.next();
                         size += 1
                     }
@@ -81,10 +320,17 @@ impl<C: CellValue> Puzzle<C> {
                 }
             }
 
-            return Some((value, size));
+            Some((value, size))
         });
 
-        let mut entries = constraint.iter().map(|c| (&c.value, c.size));
+        match kind {
+            ClueKind::Blocks => {}
+            ClueKind::Total => {
+                return groups.map(|(_, size)| size).sum::<usize>() == constraint.iter().map(|c| c.size).sum::<usize>()
+            }
+            ClueKind::Count => return groups.count() == constraint.iter().filter(|c| c.size > 0).count(),
+        }
+        let mut entries = constraint.iter().map(|c| (c.value, c.size));
 
         loop {
             match (entries.next(), groups.next()) {
@@ -99,32 +345,192 @@ impl<C: CellValue> Puzzle<C> {
         }
     }
 
+    /// Checks whether `line` is valid in `board`. Lines in a band of mega clues are only valid
+    /// once the whole band is, and lines with unknown clues are always valid.
+    pub fn line_is_solved<G: Grid<C>>(&self, board: &G, line: LineId) -> bool {
+        if !self.is_clue_known(line) {
+            return true;
+        }
+        if let Some(first) = self.mega_band(line) {
+            let lines = [first, LineId::new(first.family, first.index + 1)];
+            let cells = lines.map(|line| {
+                self.geometry
+                    .line(line)
+                    .into_iter()
+                    .map(|(row, col)| board.cell(row, col))
+                    .collect::<Vec<_>>()
+            });
+            let clues = self.mega.iter().filter(|clue| clue.line == first).collect::<Vec<_>>();
+            return band_is_solved(lines.map(|line| self.constraint(line)), &clues, [&cells[0], &cells[1]]);
+        }
+        let constraint = self.constraint(line);
+        let kind = self.clue_kind(line);
+        if self.wraps {
+            let cells = self
+                .geometry
+                .line(line)
+                .into_iter()
+                .map(|(row, col)| board.cell(row, col));
+            return Self::is_solved(kind, constraint, unwrap_line(cells.collect()));
+        }
+        match (self.geometry, line.family) {
+            (Geometry::Square { .. }, 0) => Self::is_solved(kind, constraint, board.row_cells(line.index)),
+            (Geometry::Square { .. }, _) => Self::is_solved(kind, constraint, board.column_cells(line.index)),
+            _ => Self::is_solved(
+                kind,
+                constraint,
+                self.geometry
+                    .line(line)
+                    .into_iter()
+                    .map(|(row, col)| board.cell(row, col)),
+            ),
+        }
+    }
+
     /// Checks whether the row in `board` at `index` is valid.
-    pub fn row_is_solved(&self, board: &Board<C>, index: usize) -> bool {
-        Self::is_solved(&self.row_constraints[index], board.row(index))
+    pub fn row_is_solved<G: Grid<C>>(&self, board: &G, index: usize) -> bool {
+        self.line_is_solved(board, LineId::new(0, index))
     }
 
     /// Checks whether the column in `board` at `index` is valid.
-    pub fn column_is_solved(&self, board: &Board<C>, index: usize) -> bool {
-        Self::is_solved(&self.column_constraints[index], board.column(index))
+    pub fn column_is_solved<G: Grid<C>>(&self, board: &G, index: usize) -> bool {
+        self.line_is_solved(board, LineId::new(1, index))
+    }
+
+    /// Returns a copy of this puzzle with `transform` applied, so that it is solved by
+    /// the transformed solutions of this puzzle.
+    ///
+    /// # Panics
+    /// Panics if this puzzle isn't on a square grid, has mega clues or wraps around, and `transform`
+    /// isn't the identity.
+    pub fn transformed(&self, transform: Transform) -> Self {
+        if transform == Transform::Identity {
+            return self.clone();
+        }
+        assert!(
+            matches!(self.geometry, Geometry::Square { .. }),
+            "only square puzzles can be transformed"
+        );
+        assert!(self.mega.is_empty(), "puzzles with mega clues can't be transformed");
+        // Which block of a wrapping line comes first depends on where the blocks are, so the
+        // clues of a reversed line can't be worked out from the clues alone.
+        assert!(!self.wraps, "puzzles with wrapping lines can't be transformed");
+        let (width, height) = self.geometry.dimensions();
+        let (new_width, new_height) = transform.dimensions(width, height);
+
+        let source = |family: usize, index: usize| match family {
+            0 => transform.source_of_row(index, width, height),
+            _ => transform.source_of_column(index, width, height),
+        };
+        let line = |(is_row, index, reversed): (bool, usize, bool)| {
+            let constraint = &self.groups[if is_row { 0 } else { 1 }][index];
+            if reversed {
+                constraint.iter().rev().copied().collect()
+            } else {
+                constraint.clone()
+            }
+        };
+        let unknown = (0..2)
+            .flat_map(|family| (0..[new_height, new_width][family]).map(move |index| LineId::new(family, index)))
+            .filter(|line| {
+                let (is_row, index, _) = source(line.family, line.index);
+                !self.is_clue_known(LineId::new(if is_row { 0 } else { 1 }, index))
+            })
+            .collect::<Vec<_>>();
+        let kinds = (0..2)
+            .flat_map(|family| (0..[new_height, new_width][family]).map(move |index| LineId::new(family, index)))
+            .map(|line| {
+                let (is_row, index, _) = source(line.family, line.index);
+                (line, self.clue_kind(LineId::new(if is_row { 0 } else { 1 }, index)))
+            })
+            .collect::<Vec<_>>();
+        let givens = self
+            .givens
+            .iter()
+            .map(|&(row, col, given)| {
+                let (row, col) = transform.map(row, col, width, height);
+                (row, col, given)
+            })
+            .collect();
+
+        Self::new(
+            (0..new_height).map(|r| line(source(0, r))).collect(),
+            (0..new_width).map(|c| line(source(1, c))).collect(),
+        )
+        .with_unknown_clues(unknown)
+        .with_givens(givens)
+        .with_clue_kinds(kinds)
+        .with_metadata(self.metadata.clone())
     }
 
-    /// Checks whether the given board is a solution for this puzzle.
+    /// Returns the transforms which map this puzzle's geometry onto itself.
+    pub(crate) fn symmetries(&self) -> &'static [Transform] {
+        match self.geometry {
+            Geometry::Square { .. } if self.mega.is_empty() && !self.wraps => &Transform::ALL,
+            _ => &[Transform::Identity],
+        }
+    }
+
+    /// Returns every value used by this puzzle's constraints, in order of first use.
+    pub(crate) fn palette(&self) -> Vec<C> {
+        let mut palette = Vec::new();
+        let values = self.groups.iter().flatten().flatten().map(|entry| entry.value);
+        let values = values
+            .chain(self.mega.iter().map(|clue| clue.value))
+            .chain(self.givens.iter().filter_map(|&(_, _, given)| given));
+        for value in values {
+            if !palette.contains(&value) {
+                palette.push(value);
+            }
+        }
+        palette
+    }
+
+    /// Whether or not this puzzle has the same constraints as `other`, regardless of metadata.
+    pub fn has_same_constraints(&self, other: &Self) -> bool {
+        self.geometry == other.geometry
+            && self.groups == other.groups
+            && self.mega == other.mega
+            && self.wraps == other.wraps
+            && self.unknown == other.unknown
+            && self.givens == other.givens
+            && self.kinds == other.kinds
+    }
+
+    /// Checks whether the given board is a solution for this puzzle, including its givens.
     /// Assumes the board has the same width and height as this puzzle.
-    pub fn is_solved_by(&self, board: &Board<C>) -> bool {
-        (0..self.row_constraints.len()).all(|i| self.row_is_solved(board, i))
-            && {
-                println!();
-                true
+    pub fn is_solved_by<G: Grid<C>>(&self, board: &G) -> bool {
+        (0..self.groups[0].len()).all(|i| self.row_is_solved(board, i))
+            && self
+                .geometry
+                .lines()
+                .filter(|line| line.family > 0)
+                .all(|line| self.line_is_solved(board, line))
+            && self.givens.iter().all(|&(row, col, given)| match given {
+                Some(value) => board.cell(row, col) == Cell::Filled(value),
+                None => board.cell(row, col).is_ignored(),
+            })
+    }
+}
+
+/// Rotates the cells of a wrapping line so that a block which wraps around is in one piece at the
+/// end, and the line can be checked like any other.
+pub(crate) fn unwrap_line<C: CellValue>(mut cells: Vec<Cell<C>>) -> Vec<Cell<C>> {
+    if let (Some(Cell::Filled(first)), Some(Cell::Filled(last))) = (cells.first(), cells.last()) {
+        if first == last {
+            let value = *first;
+            if let Some(start) = cells.iter().position(|cell| *cell != Cell::Filled(value)) {
+                cells.rotate_left(start);
             }
-            && (0..self.column_constraints.len()).all(|i| self.column_is_solved(board, i))
+        }
     }
+    cells
 }
 
 #[cfg(test)]
 mod tests {
     use crate::puzzle::ConstraintGroup;
-    use crate::{Board, Puzzle};
+    use crate::{Board, ClueKind, LineId, Puzzle};
 
     #[derive(PartialEq, Copy, Clone, Debug)]
     struct SimpleCell;
@@ -143,10 +549,10 @@ mod tests {
 
     fn test_puzzle() -> Puzzle<SimpleCell> {
         fn make(constraints: Vec<Vec<(usize, SimpleCell)>>) -> ConstraintGroup<SimpleCell> {
-            return constraints
+            constraints
                 .into_iter()
                 .map(|constraint| constraint.into_iter().map(From::from).collect())
-                .collect();
+                .collect()
         }
 
         #[rustfmt::skip]
@@ -191,4 +597,78 @@ mod tests {
 
         assert!(!puzzle.is_solved_by(&board));
     }
+
+    #[test]
+    fn blocks_wrap_around() {
+        use crate::Cell::*;
+        let filled = Filled(SimpleCell);
+        #[rustfmt::skip]
+        let board = Board::new_raw(vec![
+            filled, Empty,  Empty,  filled,
+            filled, filled, Empty,  filled,
+        ], 4, 2);
+
+        let one = vec![(1, SimpleCell).into()];
+        let puzzle = Puzzle::new(
+            vec![vec![(2, SimpleCell).into()], vec![(3, SimpleCell).into()]],
+            vec![
+                vec![(2, SimpleCell).into()],
+                one.clone(),
+                vec![],
+                vec![(2, SimpleCell).into()],
+            ],
+        );
+        assert!(!puzzle.is_solved_by(&board));
+        assert!(puzzle.clone().with_wrapping(true).is_solved_by(&board));
+
+        // The blocks either side of the edge are one block, so the second row isn't 2 1.
+        let puzzle = Puzzle::new(
+            vec![
+                vec![(2, SimpleCell).into()],
+                vec![(2, SimpleCell).into(), (1, SimpleCell).into()],
+            ],
+            vec![vec![(2, SimpleCell).into()], one, vec![], vec![(2, SimpleCell).into()]],
+        )
+        .with_wrapping(true);
+        assert!(!puzzle.row_is_solved(&board, 1));
+    }
+
+    #[test]
+    fn total_and_count_clues() {
+        use crate::Cell::*;
+        let filled = Filled(SimpleCell);
+        let board = Board::new_raw(vec![filled, Empty, filled, filled], 4, 1);
+
+        let one = vec![(1, SimpleCell).into()];
+        let puzzle = Puzzle::new(
+            vec![vec![(3, SimpleCell).into()]],
+            vec![one.clone(), vec![], one.clone(), one],
+        );
+        assert!(!puzzle.is_solved_by(&board));
+        let total = puzzle
+            .clone()
+            .with_clue_kinds(vec![(LineId::new(0, 0), ClueKind::Total)]);
+        assert!(total.is_solved_by(&board));
+        let count = puzzle.with_clue_kinds(vec![(LineId::new(0, 0), ClueKind::Count)]);
+        assert!(!count.is_solved_by(&board));
+        assert_eq!(count.clue_kind(LineId::new(1, 0)), ClueKind::Blocks);
+    }
+
+    #[test]
+    #[should_panic]
+    fn givens_must_be_cells() {
+        test_puzzle().with_givens(vec![(3, 0, None)]);
+    }
+
+    #[test]
+    fn givens_are_sorted() {
+        let puzzle = test_puzzle().with_givens(vec![(1, 0, None), (0, 2, Some(SimpleCell)), (1, 0, None)]);
+        assert_eq!(puzzle.givens(), [(0, 2, Some(SimpleCell)), (1, 0, None)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn givens_must_agree() {
+        test_puzzle().with_givens(vec![(0, 0, None), (0, 0, Some(SimpleCell))]);
+    }
 }
@@ -0,0 +1,82 @@
+use core::time::Duration;
+
+/// Statistics about a game.
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+pub struct Stats {
+    /// The time spent playing, excluding pauses.
+    pub elapsed: Duration,
+    /// The number of moves made.
+    pub moves: usize,
+    /// The number of hints used.
+    pub hints: usize,
+    /// The number of mistakes made.
+    pub mistakes: usize,
+}
+
+/// A way of scoring a solved game.
+pub trait Scoring {
+    /// Scores a game solved with the given stats.
+    fn score(&self, stats: &Stats) -> u64;
+}
+
+/// The default scoring model.
+///
+/// Starts from [`BASE`](DefaultScoring::BASE) and deducts one point per second,
+/// plus a fixed penalty for each hint and mistake.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DefaultScoring;
+
+impl DefaultScoring {
+    /// The score of an instant, perfect solve.
+    pub const BASE: u64 = 10_000;
+    /// The penalty for using a hint.
+    pub const HINT_PENALTY: u64 = 250;
+    /// The penalty for making a mistake.
+    pub const MISTAKE_PENALTY: u64 = 500;
+}
+
+impl Scoring for DefaultScoring {
+    fn score(&self, stats: &Stats) -> u64 {
+        Self::BASE
+            .saturating_sub(stats.elapsed.as_secs())
+            .saturating_sub(stats.hints as u64 * Self::HINT_PENALTY)
+            .saturating_sub(stats.mistakes as u64 * Self::MISTAKE_PENALTY)
+    }
+}
+
+/// The result of solving a game.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Report {
+    /// The stats at the moment the game was solved.
+    pub stats: Stats,
+    /// The score given by the game's scoring model.
+    pub score: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DefaultScoring, Scoring, Stats};
+    use std::time::Duration;
+
+    #[test]
+    fn default_scoring_works() {
+        let stats = Stats {
+            elapsed: Duration::from_secs(100),
+            moves: 42,
+            hints: 1,
+            mistakes: 2,
+        };
+
+        assert_eq!(DefaultScoring.score(&stats), 10_000 - 100 - 250 - 1_000);
+    }
+
+    #[test]
+    fn default_scoring_saturates() {
+        let stats = Stats {
+            mistakes: 100,
+            ..Stats::default()
+        };
+
+        assert_eq!(DefaultScoring.score(&stats), 0);
+    }
+}
@@ -0,0 +1,1166 @@
+use crate::budget::Limits;
+use crate::cell::CellValue;
+use crate::mega::band_is_solved;
+use crate::{Board, Budget, Cell, ClueKind, Constraint, Geometry, Grid, LineId, MegaClue, Puzzle};
+use alloc::borrow::ToOwned;
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use itertools::iproduct;
+
+/// The bit of a cell's domain which means it can be left empty.
+const BACKGROUND: u32 = 1;
+
+/// The reasoning used to make a solver step.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Technique {
+    /// The line has no blocks, so every cell is empty.
+    EmptyLine,
+    /// The blocks and the gaps between them fill the line exactly.
+    FullLine,
+    /// With nothing known in the line, the blocks overlap wherever they are placed.
+    Overlap,
+    /// Every block is already placed, so the rest of the line is empty.
+    Completion,
+    /// Every placement of the blocks which fits the known cells agrees on some cells.
+    Combination,
+}
+
+/// A single deduction made by the solver.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step<C: CellValue> {
+    /// The line which was examined.
+    pub line: LineId,
+    /// How the cells were determined.
+    pub technique: Technique,
+    /// The cells which were determined, as their row, column and value.
+    /// Cells which must be empty are crossed out.
+    pub cells: Vec<(usize, usize, Cell<C>)>,
+    /// An English explanation of the step.
+    pub explanation: String,
+}
+
+/// How far the solver got.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    /// Every cell was determined.
+    Solved,
+    /// Some cells could not be determined line by line. The puzzle may have several
+    /// solutions, or need guessing.
+    Stuck,
+    /// The clues contradict each other, so the puzzle has no solution.
+    Contradiction,
+    /// The solver ran out of time or was cancelled.
+    GaveUp,
+}
+
+/// The result of solving a puzzle, with its cells stored in a `G`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Solution<C: CellValue, G = Board<C>> {
+    /// How far the solver got.
+    pub outcome: Outcome,
+    /// Every cell which was determined. Filled cells are filled, cells which must be empty
+    /// are crossed out, and unknown cells are empty.
+    pub board: G,
+    /// The steps taken, in order, if a trace was requested.
+    pub trace: Vec<Step<C>>,
+}
+
+/// The solutions of a puzzle, stored in a `G`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Solutions<G> {
+    /// The puzzle has no solution.
+    None,
+    /// The puzzle has exactly one solution.
+    Unique(G),
+    /// The puzzle has two or more solutions. Two of them are given.
+    Multiple(G, G),
+    /// The search ran out of budget or was cancelled before it could tell.
+    GaveUp {
+        /// The first solution, if one was found.
+        solution: Option<G>,
+        /// The cells which were determined without guessing, as in [`Solution::board`].
+        progress: G,
+    },
+}
+
+/// The result of searching for a puzzle's solutions, with them stored in a `G`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Search<G> {
+    /// The solutions found.
+    pub solutions: Solutions<G>,
+    /// The number of times a cell's value had to be guessed.
+    pub guesses: usize,
+    /// The number of positions searched, each of which follows a guess, apart from the first.
+    pub nodes: usize,
+}
+
+/// A block of a clue: its size and the domain bit of its value.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Block {
+    size: usize,
+    bit: u32,
+}
+
+/// A line of the puzzle being solved.
+struct SolverLine {
+    id: LineId,
+    kind: ClueKind,
+    blocks: Vec<Block>,
+    // The indices of the line's cells in the state.
+    cells: Vec<usize>,
+}
+
+/// A band of mega clues, and the constraints of its two lines.
+struct Band<C> {
+    first: LineId,
+    constraints: [Constraint<C>; 2],
+    clues: Vec<MegaClue<C>>,
+}
+
+/// A logical line solver, which repeatedly deduces cells one line at a time.
+///
+/// Lines in a band of [mega clues](MegaClue) can't be solved on their own. Their cells are only
+/// deduced from the lines crossing them, and the band is checked once every cell is known.
+///
+/// Solutions are given as a [`Board`] unless another [`Grid`] is chosen with
+/// [`with_grid`](Solver::with_grid), such as a [`BitBoard`](crate::BitBoard) for large black and
+/// white puzzles.
+pub struct Solver<C: CellValue, G = Board<C>> {
+    geometry: Geometry,
+    palette: Vec<C>,
+    lines: Vec<SolverLine>,
+    // The indices of the lines through each cell.
+    crossings: Vec<Vec<usize>>,
+    // The bands of mega clues, which are only checked once every cell is known.
+    bands: Vec<Band<C>>,
+    wraps: bool,
+    // The domains of the given cells, by index.
+    givens: Vec<(usize, u32)>,
+    trace: bool,
+    budget: Budget,
+    grid: PhantomData<G>,
+}
+
+impl<C: CellValue> Solver<C> {
+    /// Creates a solver for `puzzle`.
+    ///
+    /// # Panics
+    /// Panics if the puzzle has more than 31 different values.
+    pub fn new(puzzle: &Puzzle<C>) -> Self {
+        let palette = puzzle.palette();
+        assert!(palette.len() < 32, "cannot solve puzzles with more than 31 values");
+
+        let bit = |value: C| 2 << palette.iter().position(|v| *v == value).unwrap();
+        let blocks = |constraint: &Constraint<C>| {
+            constraint
+                .iter()
+                .filter(|entry| entry.size > 0)
+                .map(|entry| Block {
+                    size: entry.size,
+                    bit: bit(entry.value),
+                })
+                .collect()
+        };
+        let geometry = *puzzle.geometry();
+        let (width, height) = geometry.dimensions();
+        let mut crossings = vec![Vec::new(); width * height];
+        let lines = geometry
+            .lines()
+            .filter(|&id| puzzle.is_clue_known(id) && puzzle.mega_band(id).is_none())
+            .enumerate()
+            .map(|(i, id)| {
+                let cells = geometry
+                    .line(id)
+                    .into_iter()
+                    .map(|(row, col)| row * width + col)
+                    .collect::<Vec<_>>();
+                for &cell in &cells {
+                    crossings[cell].push(i);
+                }
+                SolverLine {
+                    id,
+                    kind: puzzle.clue_kind(id),
+                    blocks: blocks(puzzle.constraint(id)),
+                    cells,
+                }
+            })
+            .collect();
+
+        let givens = puzzle
+            .givens()
+            .iter()
+            .map(|&(row, col, given)| {
+                let domain = match given {
+                    Some(value) => bit(value),
+                    None => BACKGROUND,
+                };
+                (row * width + col, domain)
+            })
+            .collect();
+
+        let mut bands = Vec::<Band<C>>::new();
+        for clue in puzzle.mega_clues() {
+            match bands.iter_mut().find(|band| band.first == clue.line) {
+                Some(band) => band.clues.push(*clue),
+                None => bands.push(Band {
+                    first: clue.line,
+                    constraints: [
+                        puzzle.constraint(clue.line).clone(),
+                        puzzle
+                            .constraint(LineId::new(clue.line.family, clue.line.index + 1))
+                            .clone(),
+                    ],
+                    clues: vec![*clue],
+                }),
+            }
+        }
+
+        Self {
+            geometry,
+            palette,
+            lines,
+            crossings,
+            bands,
+            wraps: puzzle.wraps(),
+            givens,
+            trace: false,
+            budget: Budget::default(),
+            grid: PhantomData,
+        }
+    }
+}
+
+impl<C: CellValue, G: Grid<C>> Solver<C, G> {
+    /// Sets whether or not to record every step in the solution's trace.
+    pub fn with_trace(self, trace: bool) -> Self {
+        Self { trace, ..self }
+    }
+
+    /// Sets the limits on how much work solving and searching may do.
+    pub fn with_budget(self, budget: Budget) -> Self {
+        Self { budget, ..self }
+    }
+
+    /// Sets the kind of grid solutions are stored in.
+    pub fn with_grid<H: Grid<C>>(self) -> Solver<C, H> {
+        Solver {
+            geometry: self.geometry,
+            palette: self.palette,
+            lines: self.lines,
+            crossings: self.crossings,
+            bands: self.bands,
+            wraps: self.wraps,
+            givens: self.givens,
+            trace: self.trace,
+            budget: self.budget,
+            grid: PhantomData,
+        }
+    }
+
+    /// Solves as much of the puzzle as possible without guessing.
+    pub fn solve(&self) -> Solution<C, G> {
+        let mut state = self.start();
+        let mut trace = Vec::new();
+        let trace_into = if self.trace { Some(&mut trace) } else { None };
+        let outcome = match self.propagate(&mut state, self.all_lines(), trace_into, &self.budget.start()) {
+            Ok(()) if state.is_solved() => Outcome::Solved,
+            Ok(()) => Outcome::Stuck,
+            Err(Halt::Contradiction) => Outcome::Contradiction,
+            Err(Halt::GaveUp) => Outcome::GaveUp,
+        };
+        Solution {
+            outcome,
+            board: self.board(&state),
+            trace,
+        }
+    }
+
+    /// Finds up to two solutions, guessing whenever solving line by line gets stuck.
+    pub fn search(&self) -> Search<G> {
+        let limits = self.budget.start();
+        let mut found = Vec::new();
+        let (mut guesses, mut nodes) = (0, 0);
+        let mut progress = None;
+        let mut stack = vec![(self.start(), self.all_lines())];
+
+        while let Some((mut state, lines)) = stack.pop() {
+            nodes += 1;
+            if limits.is_exceeded_by(nodes) {
+                nodes -= 1;
+                return self.give_up(found, progress, guesses, nodes);
+            }
+            match self.propagate(&mut state, lines, None, &limits) {
+                Ok(()) => {}
+                Err(Halt::Contradiction) => continue,
+                Err(Halt::GaveUp) => return self.give_up(found, progress, guesses, nodes),
+            }
+            if progress.is_none() {
+                progress = Some(self.board(&state));
+            }
+            let index = match state.domains.iter().position(|domain| !domain.is_power_of_two()) {
+                Some(index) => index,
+                None => {
+                    found.push(self.board(&state));
+                    if found.len() == 2 {
+                        break;
+                    }
+                    continue;
+                }
+            };
+
+            // The stack is last in, first out, so values are tried from highest to lowest, and empty last.
+            guesses += 1;
+            let domain = state.domains[index];
+            for bit in (0..32).map(|i| 1 << i).filter(|bit| domain & bit != 0) {
+                let mut guess = state.clone();
+                guess.domains[index] = bit;
+                stack.push((guess, self.crossings[index].iter().copied().collect()));
+            }
+        }
+
+        let mut found = found.into_iter();
+        let solutions = match (found.next(), found.next()) {
+            (None, _) => Solutions::None,
+            (Some(solution), None) => Solutions::Unique(solution),
+            (Some(first), Some(second)) => Solutions::Multiple(first, second),
+        };
+        Search {
+            solutions,
+            guesses,
+            nodes,
+        }
+    }
+
+    fn give_up(&self, found: Vec<G>, progress: Option<G>, guesses: usize, nodes: usize) -> Search<G> {
+        Search {
+            solutions: Solutions::GaveUp {
+                solution: found.into_iter().next(),
+                progress: progress.unwrap_or_else(|| self.board(&self.start())),
+            },
+            guesses,
+            nodes,
+        }
+    }
+
+    /// Returns the state where only the givens are known. Positions which aren't cells are empty.
+    pub(crate) fn start(&self) -> State {
+        let all = (2 << self.palette.len()) - 1;
+        let (width, height) = self.geometry.dimensions();
+        let mut domains = (0..width * height)
+            .map(|i| {
+                if self.geometry.contains(i / width, i % width) {
+                    all
+                } else {
+                    BACKGROUND
+                }
+            })
+            .collect::<Vec<_>>();
+        for &(index, domain) in &self.givens {
+            domains[index] = domain;
+        }
+        State { domains }
+    }
+
+    /// Returns the board described by `state`.
+    pub(crate) fn board(&self, state: &State) -> G {
+        let (width, height) = self.geometry.dimensions();
+        let mut board = G::new_empty(width, height);
+        for (index, &domain) in state.domains.iter().enumerate() {
+            let (row, col) = (index / width, index % width);
+            if self.geometry.contains(row, col) {
+                board.set_cell(row, col, self.cell(domain));
+            }
+        }
+        board
+    }
+
+    fn cell(&self, domain: u32) -> Cell<C> {
+        if domain == BACKGROUND {
+            Cell::CrossedOut
+        } else if domain.is_power_of_two() {
+            Cell::Filled(self.palette[domain.trailing_zeros() as usize - 1])
+        } else {
+            Cell::Empty
+        }
+    }
+
+    /// Returns the index of every line of the puzzle.
+    fn all_lines(&self) -> VecDeque<usize> {
+        (0..self.lines.len()).collect()
+    }
+
+    /// Deduces cells line by line, starting with the lines at the indices in `queue`, until
+    /// nothing more can be found.
+    pub(crate) fn propagate(
+        &self,
+        state: &mut State,
+        mut queue: VecDeque<usize>,
+        mut trace: Option<&mut Vec<Step<C>>>,
+        limits: &Limits<'_>,
+    ) -> Result<(), Halt> {
+        let width = self.geometry.dimensions().0;
+        let mut queued = vec![false; self.lines.len()];
+        for &line in &queue {
+            queued[line] = true;
+        }
+
+        while let Some(index) = queue.pop_front() {
+            if limits.is_interrupted() {
+                return Err(Halt::GaveUp);
+            }
+            queued[index] = false;
+            let line = &self.lines[index];
+
+            let before = line.cells.iter().map(|&cell| state.domains[cell]).collect::<Vec<_>>();
+            let after = match line.kind {
+                ClueKind::Blocks if self.wraps => solve_wrapping_line(&line.blocks, &before),
+                ClueKind::Blocks => solve_line(&line.blocks, &before),
+                ClueKind::Total => solve_total_line(line.blocks.iter().map(|block| block.size).sum(), &before),
+                ClueKind::Count => solve_count_line(line.blocks.len(), &before, self.wraps),
+            }
+            .ok_or(Halt::Contradiction)?;
+            if after == before {
+                continue;
+            }
+
+            let mut determined = Vec::new();
+            for (i, &cell) in line.cells.iter().enumerate() {
+                if after[i] == before[i] {
+                    continue;
+                }
+                state.domains[cell] = after[i];
+                if after[i].is_power_of_two() {
+                    determined.push((cell / width, cell % width, self.cell(after[i])));
+                }
+
+                for &crossing in &self.crossings[cell] {
+                    if !queued[crossing] {
+                        queued[crossing] = true;
+                        queue.push_back(crossing);
+                    }
+                }
+            }
+
+            if let Some(trace) = trace.as_mut() {
+                if !determined.is_empty() {
+                    trace.push(self.step(line, &before, &after, determined));
+                }
+            }
+        }
+
+        if state.is_solved() && !self.bands_hold(state) {
+            return Err(Halt::Contradiction);
+        }
+        Ok(())
+    }
+
+    /// Whether or not every band of mega clues is satisfied by the fully known `state`.
+    fn bands_hold(&self, state: &State) -> bool {
+        let width = self.geometry.dimensions().0;
+        self.bands.iter().all(|band| {
+            let cells = [band.first, LineId::new(band.first.family, band.first.index + 1)].map(|line| {
+                self.geometry
+                    .line(line)
+                    .into_iter()
+                    .map(|(row, col)| self.cell(state.domains[row * width + col]))
+                    .collect::<Vec<_>>()
+            });
+            band_is_solved(
+                [&band.constraints[0], &band.constraints[1]],
+                &band.clues.iter().collect::<Vec<_>>(),
+                [&cells[0], &cells[1]],
+            )
+        })
+    }
+
+    fn step(
+        &self,
+        solver_line: &SolverLine,
+        before: &[u32],
+        after: &[u32],
+        cells: Vec<(usize, usize, Cell<C>)>,
+    ) -> Step<C> {
+        let (line, blocks) = (solver_line.id, &solver_line.blocks);
+        let clue = match solver_line.kind {
+            ClueKind::Total => format!("total {}", blocks.iter().map(|block| block.size).sum::<usize>()),
+            ClueKind::Count => format!("count {}", blocks.len()),
+            ClueKind::Blocks if blocks.is_empty() => "0".to_owned(),
+            ClueKind::Blocks => blocks
+                .iter()
+                .map(|block| block.size.to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
+        let filled = cells.iter().filter(|(_, _, cell)| !cell.is_ignored()).count();
+        let empty = cells.len() - filled;
+        let cells_text = |count: usize| {
+            if count == 1 {
+                "1 cell".to_owned()
+            } else {
+                format!("{} cells", count)
+            }
+        };
+        let line_name = self.geometry.describe(line).to_string();
+        let name = line_name[..1].to_uppercase() + &line_name[1..];
+
+        let technique = if blocks.is_empty() {
+            Technique::EmptyLine
+        } else if solver_line.kind != ClueKind::Blocks {
+            Technique::Combination
+        } else if !self.wraps && minimum_length(blocks) == before.len() {
+            Technique::FullLine
+        } else if before.iter().all(|domain| !domain.is_power_of_two()) {
+            Technique::Overlap
+        } else if filled == 0 && after.iter().all(|domain| domain.is_power_of_two()) {
+            Technique::Completion
+        } else {
+            Technique::Combination
+        };
+
+        let explanation = match technique {
+            Technique::EmptyLine => format!("{} has no blocks, so {} must be empty.", name, cells_text(empty)),
+            Technique::FullLine => format!(
+                "{}'s clue {} fills the whole line exactly, so every cell is determined.",
+                name, clue
+            ),
+            Technique::Overlap => format!(
+                "In {}, the blocks of clue {} overlap however they are placed, so {} must be filled.",
+                line_name,
+                clue,
+                cells_text(filled)
+            ),
+            Technique::Completion => format!(
+                "{} already contains every block of clue {}, so the remaining {} must be empty.",
+                name,
+                clue,
+                cells_text(empty)
+            ),
+            Technique::Combination => format!(
+                "Every placement of clue {} in {} which fits the known cells agrees on {}: {} filled and {} empty.",
+                clue,
+                line_name,
+                cells_text(cells.len()),
+                filled,
+                empty
+            ),
+        };
+
+        Step {
+            line,
+            technique,
+            cells,
+            explanation,
+        }
+    }
+}
+
+impl<C: CellValue> Puzzle<C> {
+    /// Solves as much of this puzzle as possible without guessing. See [`Solver`] for more options.
+    pub fn solve(&self) -> Solution<C> {
+        Solver::new(self).solve()
+    }
+}
+
+/// What is known about every cell of a puzzle while solving it.
+///
+/// Each cell has a domain: a bitset of the values it can still take, where bit 0 is
+/// empty and bit `i + 1` is the solver's `i`th value.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct State {
+    // In row-major order, like a board.
+    domains: Vec<u32>,
+}
+
+impl State {
+    /// Whether or not every cell has a single possible value.
+    pub(crate) fn is_solved(&self) -> bool {
+        self.domains.iter().all(|domain| domain.is_power_of_two())
+    }
+}
+
+/// Why solving stopped early.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Halt {
+    /// The clues can't all be satisfied.
+    Contradiction,
+    /// The budget ran out.
+    GaveUp,
+}
+
+/// The shortest line `blocks` fit in.
+fn minimum_length(blocks: &[Block]) -> usize {
+    let gaps = blocks.windows(2).filter(|pair| pair[0].bit == pair[1].bit).count();
+    blocks.iter().map(|block| block.size).sum::<usize>() + gaps
+}
+
+/// Narrows the domains of a line's cells to the values they take in at least one placement
+/// of `blocks`. Returns `None` if the blocks can't be placed at all.
+fn solve_line(blocks: &[Block], line: &[u32]) -> Option<Vec<u32>> {
+    let (n, k) = (line.len(), blocks.len());
+    let background = |i: usize| line[i] & BACKGROUND != 0;
+
+    // misses[j][i] counts the cells before i which can't take block j's value.
+    let misses = blocks
+        .iter()
+        .map(|block| {
+            let mut misses = vec![0; n + 1];
+            for i in 0..n {
+                misses[i + 1] = misses[i] + (line[i] & block.bit == 0) as usize;
+            }
+            misses
+        })
+        .collect::<Vec<_>>();
+    let fits = |j: usize, start: usize| {
+        let end = start + blocks[j].size;
+        end <= n && misses[j][end] == misses[j][start]
+    };
+    let touching = |j: usize| blocks[j].bit == blocks[j + 1].bit;
+
+    // before[j][i]: blocks 0..j fit in cells 0..i, with the rest empty.
+    let mut before = vec![vec![false; n + 1]; k + 1];
+    before[0][0] = true;
+    for i in 1..=n {
+        before[0][i] = before[0][i - 1] && background(i - 1);
+    }
+    // after[j][i]: blocks j..k fit in cells i..n, with the rest empty.
+    let mut after = vec![vec![false; n + 1]; k + 1];
+    after[k][n] = true;
+    for i in (0..n).rev() {
+        after[k][i] = after[k][i + 1] && background(i);
+    }
+
+    let fits_before = |before: &[Vec<bool>], j: usize, start: usize| {
+        if j > 0 && touching(j - 1) {
+            start >= 1 && background(start - 1) && before[j][start - 1]
+        } else {
+            before[j][start]
+        }
+    };
+    let fits_after = |after: &[Vec<bool>], j: usize, start: usize| {
+        let end = start + blocks[j].size;
+        if j + 1 < k && touching(j) {
+            end < n && background(end) && after[j + 1][end + 1]
+        } else {
+            after[j + 1][end]
+        }
+    };
+
+    for j in 0..k {
+        for i in 0..=n {
+            let size = blocks[j].size;
+            before[j + 1][i] = (i >= 1 && background(i - 1) && before[j + 1][i - 1])
+                || (i >= size && fits(j, i - size) && fits_before(&before, j, i - size));
+        }
+    }
+    if !before[k][n] {
+        return None;
+    }
+    for j in (0..k).rev() {
+        for i in (0..=n).rev() {
+            after[j][i] = (i < n && background(i) && after[j][i + 1]) || (fits(j, i) && fits_after(&after, j, i));
+        }
+    }
+
+    let mut result = vec![0; n];
+    for (i, domain) in result.iter_mut().enumerate() {
+        if background(i) && (0..=k).any(|j| before[j][i] && after[j][i + 1]) {
+            *domain |= BACKGROUND;
+        }
+    }
+    for (j, block) in blocks.iter().enumerate() {
+        let mut covered = vec![0isize; n + 1];
+        for start in 0..n {
+            if fits(j, start) && fits_before(&before, j, start) && fits_after(&after, j, start) {
+                covered[start] += 1;
+                covered[start + block.size] -= 1;
+            }
+        }
+        let mut count = 0;
+        for (i, domain) in result.iter_mut().enumerate() {
+            count += covered[i];
+            if count > 0 {
+                *domain |= block.bit;
+            }
+        }
+    }
+    Some(result)
+}
+
+/// Like [`solve_line`], for a line which wraps around, so its last block may continue from the
+/// last cell to the first.
+fn solve_wrapping_line(blocks: &[Block], line: &[u32]) -> Option<Vec<u32>> {
+    let n = line.len();
+    if n == 0 {
+        // Nothing wraps around an empty line, which only fits no blocks at all.
+        return solve_line(blocks, line);
+    }
+    let mut result: Option<Vec<u32>> = None;
+    let mut merge = |solution: Vec<u32>| {
+        result = Some(match result.take() {
+            Some(result) => result.iter().zip(&solution).map(|(a, b)| a | b).collect(),
+            None => solution,
+        });
+    };
+
+    // No block wraps. If the first and last blocks could join across the edge, one end is empty.
+    match (blocks.first(), blocks.last()) {
+        (Some(first), Some(last)) if blocks.len() > 1 && first.bit == last.bit => {
+            for end in [0, n - 1] {
+                let mut line = line.to_vec();
+                line[end] &= BACKGROUND;
+                if let Some(solution) = solve_line(blocks, &line) {
+                    merge(solution);
+                }
+            }
+        }
+        _ => {
+            if let Some(solution) = solve_line(blocks, line) {
+                merge(solution);
+            }
+        }
+    }
+
+    // The last block wraps, with `tail` cells at the end of the line and the rest at the start.
+    let wrapping = blocks.split_last().filter(|(last, _)| last.size < n);
+    if let Some((last, rest)) = wrapping {
+        for tail in 1..last.size {
+            let (head, end) = (last.size - tail, n - tail);
+            if (0..head).chain(end..n).any(|i| line[i] & last.bit == 0) {
+                continue;
+            }
+            // The cells either side of the wrapping block can't continue it.
+            let mut middle = line[head..end].to_vec();
+            let len = middle.len();
+            middle[0] &= !last.bit;
+            middle[len - 1] &= !last.bit;
+            if middle.contains(&0) {
+                continue;
+            }
+            if let Some(solved) = solve_line(rest, &middle) {
+                let mut solution = vec![last.bit; n];
+                solution[head..end].copy_from_slice(&solved);
+                merge(solution);
+            }
+        }
+    }
+    result
+}
+
+/// Narrows the domains of a line's cells, which must have `total` filled cells between them.
+/// Returns `None` if they can't.
+fn solve_total_line(total: usize, line: &[u32]) -> Option<Vec<u32>> {
+    let filled = line.iter().filter(|&&domain| domain & BACKGROUND == 0).count();
+    let possible = line.iter().filter(|&&domain| domain & !BACKGROUND != 0).count();
+    if line.contains(&0) || filled > total || possible < total {
+        return None;
+    }
+    Some(
+        line.iter()
+            .map(|&domain| match domain & BACKGROUND {
+                // Every cell which has to be filled is, so the rest are empty.
+                BACKGROUND if filled == total => BACKGROUND,
+                // Every cell which could be filled is needed.
+                BACKGROUND if possible == total && domain != BACKGROUND => domain & !BACKGROUND,
+                _ => domain,
+            })
+            .collect(),
+    )
+}
+
+/// Narrows the domains of a line's cells, which must form `count` blocks between them. If the
+/// line `wraps`, blocks at either end of the same value join into one. Returns `None` if they
+/// can't.
+fn solve_count_line(count: usize, line: &[u32], wraps: bool) -> Option<Vec<u32>> {
+    let n = line.len();
+    if n == 0 {
+        return Some(Vec::new()).filter(|_| count == 0);
+    }
+
+    // After each cell, the state is the value of the first cell if the line wraps, the number of
+    // blocks so far, and the value of the last cell, given by its bit index.
+    let values = (32 - line.iter().fold(0, |all, domain| all | domain).leading_zeros()) as usize;
+    let firsts = if wraps { values } else { 1 };
+    let most = count + wraps as usize;
+    let states = iproduct!(0..firsts, 0..=most, 0..values).collect::<Vec<_>>();
+    let index = |(first, blocks, last): (usize, usize, usize)| (first * (most + 1) + blocks) * values + last;
+    let allows = |i: usize, value: usize| line[i] & (1 << value) != 0;
+    let start = |value: usize| (if wraps { value } else { 0 }, (value != 0) as usize, value);
+    let next = |(first, blocks, last): (usize, usize, usize), value: usize| {
+        let blocks = blocks + (value != 0 && value != last) as usize;
+        Some((first, blocks, value)).filter(|_| blocks <= most)
+    };
+    let accepts = |(first, blocks, last): (usize, usize, usize)| {
+        let joined = wraps && first != 0 && first == last && blocks > 1;
+        blocks - joined as usize == count
+    };
+
+    // forward[i][s]: the values of cells 0..i can lead to state s.
+    let mut forward = vec![vec![false; states.len()]; n + 1];
+    for value in (0..values).filter(|&value| allows(0, value)) {
+        forward[1][index(start(value))] = true;
+    }
+    for i in 1..n {
+        let (done, rest) = forward.split_at_mut(i + 1);
+        for &state in states.iter().filter(|&&state| done[i][index(state)]) {
+            for value in (0..values).filter(|&value| allows(i, value)) {
+                if let Some(next) = next(state, value) {
+                    rest[0][index(next)] = true;
+                }
+            }
+        }
+    }
+    // backward[i][s]: from state s, the values of cells i..n can finish the line.
+    let mut backward = vec![vec![false; states.len()]; n + 1];
+    for &state in &states {
+        backward[n][index(state)] = accepts(state);
+    }
+    for i in (1..n).rev() {
+        for &state in &states {
+            backward[i][index(state)] = (0..values)
+                .any(|value| allows(i, value) && next(state, value).is_some_and(|next| backward[i + 1][index(next)]));
+        }
+    }
+
+    let mut result = vec![0; n];
+    for value in (0..values).filter(|&value| allows(0, value)) {
+        if backward[1][index(start(value))] {
+            result[0] |= 1 << value;
+        }
+    }
+    for i in 1..n {
+        for &state in states.iter().filter(|&&state| forward[i][index(state)]) {
+            for value in (0..values).filter(|&value| allows(i, value)) {
+                if next(state, value).is_some_and(|next| backward[i + 1][index(next)]) {
+                    result[i] |= 1 << value;
+                }
+            }
+        }
+    }
+    Some(result).filter(|result| !result.contains(&0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve_count_line, solve_line, solve_total_line, solve_wrapping_line, Block, BACKGROUND};
+    use crate::{
+        constraints, BitBoard, Budget, Cell, ClueKind, ConstraintEntry, Geometry, LineId, Outcome, Puzzle, SimpleCell,
+        Solutions, Solver, Technique,
+    };
+
+    const FILLED: u32 = 2;
+    const UNKNOWN: u32 = BACKGROUND | FILLED;
+
+    fn blocks(sizes: &[usize]) -> Vec<Block> {
+        sizes.iter().map(|&size| Block { size, bit: FILLED }).collect()
+    }
+
+    fn letter_f() -> Puzzle<SimpleCell> {
+        // #####
+        // #
+        // ###
+        // #
+        // #
+        Puzzle::new(
+            constraints![
+                [5, SimpleCell]
+                [1, SimpleCell]
+                [3, SimpleCell]
+                [1, SimpleCell]
+                [1, SimpleCell]
+            ],
+            constraints![
+                [5, SimpleCell]
+                [1, SimpleCell; 1, SimpleCell]
+                [1, SimpleCell; 1, SimpleCell]
+                [1, SimpleCell]
+                [1, SimpleCell]
+            ],
+        )
+    }
+
+    #[test]
+    fn line_overlap() {
+        let line = solve_line(&blocks(&[3]), &[UNKNOWN; 4]).unwrap();
+        assert_eq!(line, [UNKNOWN, FILLED, FILLED, UNKNOWN]);
+
+        let line = solve_line(&blocks(&[2, 1]), &[UNKNOWN; 4]).unwrap();
+        assert_eq!(line, [FILLED, FILLED, BACKGROUND, FILLED]);
+    }
+
+    #[test]
+    fn line_uses_known_cells() {
+        let line = solve_line(&blocks(&[2]), &[UNKNOWN, UNKNOWN, FILLED, UNKNOWN, UNKNOWN]).unwrap();
+        assert_eq!(line, [BACKGROUND, UNKNOWN, FILLED, UNKNOWN, BACKGROUND]);
+
+        assert_eq!(solve_line(&blocks(&[2]), &[FILLED, BACKGROUND, FILLED]), None);
+    }
+
+    #[test]
+    fn line_colours_may_touch() {
+        let red = Block { size: 1, bit: 2 };
+        let blue = Block { size: 2, bit: 4 };
+        let unknown = BACKGROUND | 2 | 4;
+        let line = solve_line(&[red, blue], &[unknown; 3]).unwrap();
+        assert_eq!(line, [2, 4, 4]);
+    }
+
+    #[test]
+    fn line_wraps_around() {
+        let line = solve_wrapping_line(&blocks(&[3]), &[UNKNOWN, BACKGROUND, UNKNOWN, UNKNOWN]).unwrap();
+        assert_eq!(line, [FILLED, BACKGROUND, FILLED, FILLED]);
+
+        // Blocks either side of the edge would join, so one of the ends is empty.
+        let line = solve_wrapping_line(&blocks(&[1, 1]), &[UNKNOWN, BACKGROUND, UNKNOWN]);
+        assert_eq!(line, None);
+        let line = solve_wrapping_line(&blocks(&[1, 2]), &[UNKNOWN; 5]).unwrap();
+        assert_eq!(line, [UNKNOWN; 5]);
+        assert_eq!(
+            solve_line(&blocks(&[2, 2]), &[UNKNOWN; 5]).unwrap(),
+            [FILLED, FILLED, BACKGROUND, FILLED, FILLED]
+        );
+        assert_eq!(solve_wrapping_line(&blocks(&[2, 2]), &[UNKNOWN; 5]), None);
+
+        assert_eq!(solve_wrapping_line(&blocks(&[1, 1]), &[]), None);
+        assert_eq!(solve_wrapping_line(&blocks(&[]), &[]), Some(vec![]));
+    }
+
+    #[test]
+    fn line_total_and_count() {
+        let line = solve_total_line(1, &[FILLED, UNKNOWN, UNKNOWN]).unwrap();
+        assert_eq!(line, [FILLED, BACKGROUND, BACKGROUND]);
+        let line = solve_total_line(3, &[UNKNOWN, BACKGROUND, UNKNOWN, UNKNOWN]).unwrap();
+        assert_eq!(line, [FILLED, BACKGROUND, FILLED, FILLED]);
+        assert_eq!(solve_total_line(1, &[FILLED, FILLED]), None);
+
+        let line = solve_count_line(2, &[UNKNOWN; 3], false).unwrap();
+        assert_eq!(line, [FILLED, BACKGROUND, FILLED]);
+        assert_eq!(solve_count_line(2, &[UNKNOWN; 3], true), None);
+        let line = solve_count_line(1, &[FILLED, UNKNOWN, FILLED], false).unwrap();
+        assert_eq!(line, [FILLED; 3]);
+        let line = solve_count_line(1, &[FILLED, UNKNOWN, FILLED], true).unwrap();
+        assert_eq!(line, [FILLED, UNKNOWN, FILLED]);
+    }
+
+    #[test]
+    fn solves_with_trace() {
+        let puzzle = letter_f();
+        let solution = Solver::new(&puzzle).with_trace(true).solve();
+
+        assert_eq!(solution.outcome, Outcome::Solved);
+        assert!(puzzle.is_solved_by(&solution.board));
+        assert_eq!(*solution.board.get(2, 3), Cell::CrossedOut);
+
+        let first = &solution.trace[0];
+        assert_eq!(first.line, LineId::new(0, 0));
+        assert_eq!(first.technique, Technique::FullLine);
+        assert_eq!(
+            first.explanation,
+            "Row 1's clue 5 fills the whole line exactly, so every cell is determined."
+        );
+
+        let determined = solution.trace.iter().map(|step| step.cells.len()).sum::<usize>();
+        assert_eq!(determined, 25);
+        assert!(puzzle.solve().trace.is_empty());
+    }
+
+    #[test]
+    fn solves_into_bitboards() {
+        let puzzle = letter_f();
+        let solution = Solver::new(&puzzle).with_grid::<BitBoard>().solve();
+        assert_eq!(solution.outcome, Outcome::Solved);
+        assert!(puzzle.is_solved_by(&solution.board));
+        assert_eq!(solution.board, BitBoard::from(&puzzle.solve().board));
+        assert_eq!(solution.board.count_filled(), 11);
+
+        match Solver::new(&puzzle).with_grid::<BitBoard>().search().solutions {
+            Solutions::Unique(bits) => assert_eq!(bits, solution.board),
+            solutions => panic!("expected a unique solution, found {:?}", solutions),
+        }
+    }
+
+    #[test]
+    fn reports_stuck_and_contradiction() {
+        let ambiguous = Puzzle::new(
+            constraints![[1, SimpleCell][1, SimpleCell]],
+            constraints![[1, SimpleCell][1, SimpleCell]],
+        );
+        let solution = ambiguous.solve();
+        assert_eq!(solution.outcome, Outcome::Stuck);
+        assert_eq!(*solution.board.get(0, 0), Cell::Empty);
+
+        let impossible = Puzzle::new(constraints![[2, SimpleCell][]], constraints![[1, SimpleCell][]]);
+        assert_eq!(impossible.solve().outcome, Outcome::Contradiction);
+    }
+
+    #[test]
+    fn search_guesses() {
+        // The bottom two rows can't be solved line by line.
+        let smiley = Puzzle::new(
+            constraints![
+                [1, SimpleCell; 1, SimpleCell]
+                [1, SimpleCell; 1, SimpleCell]
+                []
+                [1, SimpleCell; 1, SimpleCell]
+                [3, SimpleCell]
+            ],
+            constraints![
+                [1, SimpleCell]
+                [2, SimpleCell; 1, SimpleCell]
+                [1, SimpleCell]
+                [2, SimpleCell; 1, SimpleCell]
+                [1, SimpleCell]
+            ],
+        );
+        assert_eq!(smiley.solve().outcome, Outcome::Stuck);
+        let search = Solver::new(&smiley).search();
+        assert!(search.guesses > 0);
+        match search.solutions {
+            Solutions::Unique(board) => assert!(smiley.is_solved_by(&board)),
+            solutions => panic!("expected a unique solution, found {:?}", solutions),
+        }
+
+        let ambiguous = Puzzle::new(
+            constraints![[1, SimpleCell][1, SimpleCell]],
+            constraints![[1, SimpleCell][1, SimpleCell]],
+        );
+        match Solver::new(&ambiguous).search().solutions {
+            Solutions::Multiple(first, second) => assert_ne!(first, second),
+            solutions => panic!("expected multiple solutions, found {:?}", solutions),
+        }
+
+        let impossible = Puzzle::new(constraints![[2, SimpleCell][]], constraints![[1, SimpleCell][]]);
+        assert_eq!(Solver::new(&impossible).search().solutions, Solutions::None);
+    }
+
+    #[test]
+    fn search_gives_up() {
+        let puzzle = Puzzle::new(
+            constraints![[1, SimpleCell][1, SimpleCell][]],
+            constraints![[1, SimpleCell][1, SimpleCell]],
+        );
+        let search = Solver::new(&puzzle)
+            .with_budget(Budget::new().with_max_nodes(1))
+            .search();
+        assert_eq!(search.nodes, 1);
+        match search.solutions {
+            Solutions::GaveUp { solution, progress } => {
+                assert_eq!(solution, None);
+                assert_eq!(*progress.get(2, 0), Cell::CrossedOut);
+                assert_eq!(*progress.get(0, 0), Cell::Empty);
+            }
+            solutions => panic!("expected to give up, found {:?}", solutions),
+        }
+
+        let search = Solver::new(&puzzle)
+            .with_budget(Budget::new().with_max_nodes(3))
+            .search();
+        assert!(matches!(search.solutions, Solutions::Multiple(..)));
+    }
+
+    #[test]
+    fn solves_wrapping_puzzles() {
+        // #..#
+        // ##.#
+        let puzzle = Puzzle::new(
+            constraints![[2, SimpleCell][3, SimpleCell]],
+            constraints![[2, SimpleCell][1, SimpleCell][][2, SimpleCell]],
+        )
+        .with_wrapping(true);
+
+        let solution = puzzle.solve();
+        assert_eq!(solution.outcome, Outcome::Solved);
+        assert!(puzzle.is_solved_by(&solution.board));
+        assert_eq!(*solution.board.get(0, 1), Cell::CrossedOut);
+        assert!(!puzzle.clone().with_wrapping(false).is_solved_by(&solution.board));
+    }
+
+    #[test]
+    fn uses_givens_and_unknown_clues() {
+        let ambiguous = Puzzle::new(
+            constraints![[1, SimpleCell][1, SimpleCell]],
+            constraints![[1, SimpleCell][1, SimpleCell]],
+        );
+        let given = ambiguous.clone().with_givens(vec![(0, 1, None)]);
+        match Solver::new(&given).search().solutions {
+            Solutions::Unique(board) => {
+                assert!(given.is_solved_by(&board));
+                assert_eq!(*board.get(0, 0), Cell::Filled(SimpleCell));
+            }
+            other => panic!("expected a unique solution, found {:?}", other),
+        }
+
+        // The second row's clue is ignored, and the columns leave it empty.
+        let unknown = Puzzle::new(
+            constraints![[2, SimpleCell][2, SimpleCell]],
+            constraints![[1, SimpleCell][1, SimpleCell]],
+        )
+        .with_unknown_clues(vec![LineId::new(0, 1)]);
+        let solution = unknown.solve();
+        assert_eq!(solution.outcome, Outcome::Solved);
+        assert!(unknown.is_solved_by(&solution.board));
+        assert_eq!(*solution.board.get(1, 0), Cell::CrossedOut);
+    }
+
+    #[test]
+    fn solves_total_and_count_clues() {
+        // #.
+        // ##
+        let puzzle = Puzzle::new(
+            constraints![[1, SimpleCell][2, SimpleCell]],
+            constraints![[2, SimpleCell][1, SimpleCell]],
+        )
+        .with_clue_kinds(vec![
+            (LineId::new(0, 0), ClueKind::Total),
+            (LineId::new(0, 1), ClueKind::Count),
+        ]);
+        let solution = Solver::new(&puzzle).with_trace(true).solve();
+        assert_eq!(solution.outcome, Outcome::Solved);
+        assert!(puzzle.is_solved_by(&solution.board));
+        assert_eq!(*solution.board.get(0, 1), Cell::CrossedOut);
+        assert!(solution
+            .trace
+            .iter()
+            .any(|step| step.explanation.contains("clue total 1")));
+    }
+
+    #[test]
+    fn solves_triddlers() {
+        // Every upwards triangle is filled, so each line alternates between filled and empty.
+        let geometry = Geometry::Triangle { size: 3 };
+        let ones = |count: usize| {
+            vec![
+                ConstraintEntry {
+                    value: SimpleCell,
+                    size: 1
+                };
+                count
+            ]
+        };
+        let puzzle = Puzzle::from_geometry(
+            geometry,
+            vec![
+                vec![ones(1), ones(2), ones(3)],
+                vec![ones(3), ones(2), ones(1)],
+                vec![ones(3), ones(2), ones(1)],
+            ],
+        );
+
+        let solution = puzzle.solve();
+        assert_eq!(solution.outcome, Outcome::Solved);
+        assert!(puzzle.is_solved_by(&solution.board));
+        for (row, col, cell) in solution.board.cells() {
+            let expected = if !geometry.contains(row, col) {
+                Cell::Empty
+            } else if col % 2 == 0 {
+                Cell::Filled(SimpleCell)
+            } else {
+                Cell::CrossedOut
+            };
+            assert_eq!(*cell, expected);
+        }
+    }
+}
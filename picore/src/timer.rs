@@ -0,0 +1,91 @@
+use core::time::Duration;
+#[cfg(feature = "std")]
+pub(crate) use std::time::Instant;
+
+/// Stands in for [`std::time::Instant`] without `std`. There is no clock, so no time passes.
+#[cfg(not(feature = "std"))]
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Instant;
+
+#[cfg(not(feature = "std"))]
+impl Instant {
+    pub(crate) fn now() -> Self {
+        Instant
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+/// A pausable stopwatch.
+pub(crate) struct Timer {
+    // `None` while paused.
+    started: Option<Instant>,
+    // Time accumulated before the last pause.
+    elapsed: Duration,
+}
+
+impl Timer {
+    /// Creates a new timer which is already running.
+    pub fn start() -> Self {
+        Self::start_at(Duration::default())
+    }
+
+    /// Creates a new running timer which has already run for `elapsed`.
+    pub fn start_at(elapsed: Duration) -> Self {
+        Self {
+            started: Some(Instant::now()),
+            elapsed,
+        }
+    }
+
+    /// Stops the timer, keeping the time elapsed so far.
+    pub fn pause(&mut self) {
+        if let Some(started) = self.started.take() {
+            self.elapsed += started.elapsed();
+        }
+    }
+
+    /// Restarts a paused timer.
+    pub fn resume(&mut self) {
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+        }
+    }
+
+    /// Whether or not the timer is paused.
+    pub fn is_paused(&self) -> bool {
+        self.started.is_none()
+    }
+
+    /// The total time the timer has been running.
+    pub fn elapsed(&self) -> Duration {
+        match self.started {
+            Some(started) => self.elapsed + started.elapsed(),
+            None => self.elapsed,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::Timer;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    #[test]
+    fn pause_stops_time() {
+        let mut timer = Timer::start();
+        timer.pause();
+        assert!(timer.is_paused());
+
+        let elapsed = timer.elapsed();
+        sleep(Duration::from_millis(5));
+        assert_eq!(timer.elapsed(), elapsed);
+
+        timer.resume();
+        sleep(Duration::from_millis(5));
+        assert!(timer.elapsed() >= elapsed + Duration::from_millis(5));
+    }
+}
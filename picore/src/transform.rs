@@ -0,0 +1,203 @@
+/// A rotation or reflection of a board.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Transform {
+    /// Leaves the board unchanged.
+    Identity,
+    /// Rotates the board 90 degrees clockwise.
+    Rotate90,
+    /// Rotates the board 180 degrees.
+    Rotate180,
+    /// Rotates the board 270 degrees clockwise.
+    Rotate270,
+    /// Mirrors the board horizontally, swapping left and right.
+    MirrorHorizontal,
+    /// Mirrors the board vertically, swapping top and bottom.
+    MirrorVertical,
+    /// Mirrors the board along its main diagonal, swapping rows and columns.
+    Transpose,
+    /// Mirrors the board along its anti-diagonal.
+    AntiTranspose,
+}
+
+impl Transform {
+    /// Every transform, starting with [`Identity`](Transform::Identity).
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::MirrorHorizontal,
+        Transform::MirrorVertical,
+        Transform::Transpose,
+        Transform::AntiTranspose,
+    ];
+
+    /// Whether or not this transform turns rows into columns and vice versa.
+    pub fn swaps_axes(self) -> bool {
+        matches!(
+            self,
+            Transform::Rotate90 | Transform::Rotate270 | Transform::Transpose | Transform::AntiTranspose
+        )
+    }
+
+    /// Returns the transform which undoes this one.
+    pub fn inverse(self) -> Self {
+        match self {
+            Transform::Rotate90 => Transform::Rotate270,
+            Transform::Rotate270 => Transform::Rotate90,
+            other => other,
+        }
+    }
+
+    /// Returns the `(width, height)` of a `width` by `height` board after this transform.
+    pub fn dimensions(self, width: usize, height: usize) -> (usize, usize) {
+        if self.swaps_axes() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Maps the position `(row, col)` on a `width` by `height` board to its position after this transform.
+    pub fn map(self, row: usize, col: usize, width: usize, height: usize) -> (usize, usize) {
+        // A board with no rows still has columns to map, and vice versa.
+        let (last_row, last_col) = (height.saturating_sub(1), width.saturating_sub(1));
+        match self {
+            Transform::Identity => (row, col),
+            Transform::Rotate90 => (col, last_row - row),
+            Transform::Rotate180 => (last_row - row, last_col - col),
+            Transform::Rotate270 => (last_col - col, row),
+            Transform::MirrorHorizontal => (row, last_col - col),
+            Transform::MirrorVertical => (last_row - row, col),
+            Transform::Transpose => (col, row),
+            Transform::AntiTranspose => (last_col - col, last_row - row),
+        }
+    }
+
+    /// Finds where the row at `index` of a transformed `width` by `height` board came from.
+    /// Returns whether it was a row (rather than a column), its index, and whether it was reversed.
+    pub(crate) fn source_of_row(self, index: usize, width: usize, height: usize) -> (bool, usize, bool) {
+        let (new_width, new_height) = self.dimensions(width, height);
+        let (row, col) = self.inverse().map(index, 0, new_width, new_height);
+        if self.swaps_axes() {
+            (false, col, row != 0)
+        } else {
+            (true, row, col != 0)
+        }
+    }
+
+    /// Finds where the column at `index` of a transformed `width` by `height` board came from.
+    /// Returns whether it was a row (rather than a column), its index, and whether it was reversed.
+    pub(crate) fn source_of_column(self, index: usize, width: usize, height: usize) -> (bool, usize, bool) {
+        let (new_width, new_height) = self.dimensions(width, height);
+        let (row, col) = self.inverse().map(0, index, new_width, new_height);
+        if self.swaps_axes() {
+            (true, row, col != 0)
+        } else {
+            (false, col, row != 0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{constraints, Board, Puzzle, Transform};
+
+    #[derive(PartialEq, Copy, Clone, Debug)]
+    struct SimpleCell;
+
+    // ##.
+    // ..#
+    fn test_board() -> Board<SimpleCell> {
+        use crate::Cell::*;
+        let filled = Filled(SimpleCell);
+
+        #[rustfmt::skip]
+        return Board::new_raw(vec![
+            filled, filled, Empty,
+            Empty,  Empty,  filled,
+        ], 3, 2);
+    }
+
+    fn test_puzzle() -> Puzzle<SimpleCell> {
+        Puzzle::new(
+            constraints![
+                [2, SimpleCell]
+                [1, SimpleCell]
+            ],
+            constraints![
+                [1, SimpleCell]
+                [1, SimpleCell]
+                [1, SimpleCell]
+            ],
+        )
+    }
+
+    #[test]
+    fn inverse_works() {
+        for &transform in &Transform::ALL {
+            let (width, height) = transform.dimensions(3, 2);
+            for (r, c) in [(0, 0), (0, 2), (1, 1)].iter().copied() {
+                let (tr, tc) = transform.map(r, c, 3, 2);
+                assert_eq!(transform.inverse().map(tr, tc, width, height), (r, c));
+            }
+        }
+    }
+
+    #[test]
+    fn rotate_board() {
+        use crate::Cell::*;
+        let filled = Filled(SimpleCell);
+
+        // .#
+        // .#
+        // #.
+        let rotated = test_board().transformed(Transform::Rotate90);
+
+        #[rustfmt::skip]
+        assert_eq!(rotated, Board::new_raw(vec![
+            Empty,  filled,
+            Empty,  filled,
+            filled, Empty,
+        ], 2, 3));
+    }
+
+    #[test]
+    fn transformed_puzzle_is_solved_by_transformed_board() {
+        let board = test_board();
+        let puzzle = test_puzzle();
+        assert!(puzzle.is_solved_by(&board));
+
+        for &transform in &Transform::ALL {
+            let board = board.transformed(transform);
+            let puzzle = puzzle.transformed(transform);
+            assert!(puzzle.is_solved_by(&board), "{:?}", transform);
+            assert_eq!(board.width(), puzzle.column_constraints().len());
+        }
+    }
+
+    #[test]
+    fn mirror_reverses_constraints() {
+        let mirrored = test_puzzle().transformed(Transform::MirrorVertical);
+        assert_eq!(
+            mirrored.row_constraints(),
+            &constraints![
+                [1, SimpleCell]
+                [2, SimpleCell]
+            ]
+        );
+    }
+
+    #[test]
+    fn transform_empty_puzzle() {
+        let puzzle = crate::parse_non("width 2\nheight 0\nrows\ncolumns\n0\n0\n").unwrap();
+        for &transform in &Transform::ALL {
+            let transformed = puzzle.transformed(transform);
+            assert_eq!(
+                transformed.row_constraints().len() * transformed.column_constraints().len(),
+                0
+            );
+        }
+        assert_eq!(crate::find_duplicates(&[puzzle], true), Vec::<Vec<usize>>::new());
+    }
+}
@@ -0,0 +1,204 @@
+use crate::cell::CellValue;
+use crate::timer::Instant;
+use crate::{Board, Budget, Outcome, Puzzle, Solutions, Solver, Technique};
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+#[cfg(feature = "std")]
+use std::thread;
+
+/// Whether a puzzle can be solved, whether its solution is unique, and how hard it is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Verification<C: CellValue> {
+    /// The puzzle's solutions.
+    pub solutions: Solutions<Board<C>>,
+    /// Whether or not the puzzle can be solved line by line, without guessing.
+    pub line_solvable: bool,
+    /// How hard the puzzle is, on the same scale as [`Metadata::difficulty`](crate::Metadata::difficulty),
+    /// or `None` if it has no solution or verifying gave up.
+    ///
+    /// Puzzles which only need simple techniques are rated 1, and other puzzles which can be
+    /// solved line by line are rated 2. Puzzles which need guessing are rated 3 or more,
+    /// growing with the logarithm of the number of guesses.
+    pub difficulty: Option<u32>,
+    /// How long verifying the puzzle took. Always zero without the `std` feature.
+    pub elapsed: Duration,
+}
+
+impl<C: CellValue> Verification<C> {
+    /// Whether or not the puzzle is known to have at least one solution.
+    pub fn is_solvable(&self) -> bool {
+        matches!(
+            self.solutions,
+            Solutions::Unique(_) | Solutions::Multiple(..) | Solutions::GaveUp { solution: Some(_), .. }
+        )
+    }
+
+    /// Whether or not the puzzle has exactly one solution.
+    pub fn is_unique(&self) -> bool {
+        matches!(self.solutions, Solutions::Unique(_))
+    }
+}
+
+impl<C: CellValue> Puzzle<C> {
+    /// Checks whether this puzzle can be solved, whether its solution is unique, and how hard it is.
+    pub fn verify(&self) -> Verification<C> {
+        self.verify_with(&Budget::default())
+    }
+
+    /// Like [`verify`](Puzzle::verify), but gives up once `budget` runs out.
+    pub fn verify_with(&self, budget: &Budget) -> Verification<C> {
+        let start = Instant::now();
+        let solution = Solver::new(self).with_trace(true).with_budget(budget.clone()).solve();
+
+        let (solutions, difficulty) = match solution.outcome {
+            Outcome::Solved => {
+                let simple = solution
+                    .trace
+                    .iter()
+                    .all(|step| step.technique != Technique::Combination);
+                (Solutions::Unique(solution.board), Some(if simple { 1 } else { 2 }))
+            }
+            Outcome::Contradiction => (Solutions::None, None),
+            Outcome::GaveUp => (
+                Solutions::GaveUp {
+                    solution: None,
+                    progress: solution.board,
+                },
+                None,
+            ),
+            Outcome::Stuck => {
+                let search = Solver::new(self).with_budget(budget.after(start.elapsed())).search();
+                let difficulty = match search.solutions {
+                    Solutions::None | Solutions::GaveUp { .. } => None,
+                    _ => Some(3 + search.guesses.max(1).ilog2()),
+                };
+                (search.solutions, difficulty)
+            }
+        };
+
+        Verification {
+            solutions,
+            line_solvable: solution.outcome == Outcome::Solved,
+            difficulty,
+            elapsed: start.elapsed(),
+        }
+    }
+}
+
+/// Verifies every puzzle with [`Puzzle::verify_with`], spread across `threads` threads, or one
+/// per CPU if `threads` is 0. Each puzzle gets its own `budget`, but cancelling it stops them all.
+///
+/// Returns the results in the same order as `puzzles`.
+#[cfg(feature = "std")]
+pub fn verify_all<'a, C, I>(puzzles: I, threads: usize, budget: &Budget) -> Vec<Verification<C>>
+where
+    C: CellValue + Send + Sync + 'a,
+    I: IntoIterator<Item = &'a Puzzle<C>>,
+{
+    let puzzles = puzzles.into_iter().collect::<Vec<_>>();
+    let threads = match threads {
+        0 => thread::available_parallelism().map_or(1, |threads| threads.get()),
+        threads => threads,
+    }
+    .min(puzzles.len());
+
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((0..puzzles.len()).map(|_| None).collect::<Vec<_>>());
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let puzzle = match puzzles.get(index) {
+                    Some(puzzle) => puzzle,
+                    None => break,
+                };
+                let verification = puzzle.verify_with(budget);
+                results.lock().unwrap()[index] = Some(verification);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|verification| verification.expect("every puzzle was verified"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{constraints, Puzzle, SimpleCell};
+    #[cfg(feature = "std")]
+    use crate::{verify_all, Budget, CancelToken, Solutions};
+
+    fn puzzles() -> Vec<Puzzle<SimpleCell>> {
+        vec![
+            // Solved by the full first row and column alone.
+            Puzzle::new(
+                constraints![[2, SimpleCell][1, SimpleCell]],
+                constraints![[2, SimpleCell][1, SimpleCell]],
+            ),
+            // Two solutions.
+            Puzzle::new(
+                constraints![[1, SimpleCell][1, SimpleCell]],
+                constraints![[1, SimpleCell][1, SimpleCell]],
+            ),
+            // No solutions.
+            Puzzle::new(constraints![[2, SimpleCell][]], constraints![[1, SimpleCell][]]),
+        ]
+    }
+
+    #[test]
+    fn verify_works() {
+        let puzzles = puzzles();
+
+        let easy = puzzles[0].verify();
+        assert!(easy.is_unique());
+        assert!(easy.line_solvable);
+        assert_eq!(easy.difficulty, Some(1));
+
+        let ambiguous = puzzles[1].verify();
+        assert!(ambiguous.is_solvable());
+        assert!(!ambiguous.is_unique());
+        assert!(!ambiguous.line_solvable);
+        assert_eq!(ambiguous.difficulty, Some(3));
+
+        let impossible = puzzles[2].verify();
+        assert!(!impossible.is_solvable());
+        assert_eq!(impossible.difficulty, None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn verify_all_keeps_order() {
+        let puzzles = puzzles().into_iter().cycle().take(30).collect::<Vec<_>>();
+        let results = verify_all(&puzzles, 4, &Budget::default());
+
+        assert_eq!(results.len(), 30);
+        for (puzzle, result) in puzzles.iter().zip(&results) {
+            assert_eq!(result.solutions, puzzle.verify().solutions);
+        }
+        assert!(matches!(results[29].solutions, Solutions::None));
+        assert!(verify_all(&puzzles[..0], 0, &Budget::default()).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn cancelled_verification_gives_up() {
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let results = verify_all(&puzzles(), 2, &Budget::new().with_cancel(cancel));
+
+        for result in results {
+            assert!(matches!(result.solutions, Solutions::GaveUp { solution: None, .. }));
+            assert!(!result.is_solvable());
+            assert_eq!(result.difficulty, None);
+        }
+    }
+}
@@ -0,0 +1,287 @@
+//! WebAssembly bindings for picore, so the game can run in a browser with the same rules as
+//! piconsole.
+//!
+//! Build with `wasm-pack build piweb`, or run the tests in node with
+//! `cargo test -p piweb --target wasm32-unknown-unknown`, which needs `wasm-bindgen-test-runner`
+//! from the `wasm-bindgen-cli` version matching `wasm-bindgen`. CI runs them this way too.
+//! Browsers have no clock picore can read, so games aren't timed; time them in JavaScript instead.
+//!
+//! Positions and line indices from JavaScript are checked before they reach picore: moves off the
+//! board throw, and queries about them return `undefined`. Any other panic is logged to the console.
+
+#![deny(missing_docs)]
+
+use picore::{parse_non, Cell, Collection, LineId, LockedError, Picross, SimpleCell};
+use wasm_bindgen::prelude::*;
+
+/// Logs panics to the console, instead of only reporting an unreachable instruction.
+#[wasm_bindgen(start)]
+fn start() {
+    console_error_panic_hook::set_once();
+}
+
+/// What a cell of a game contains.
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CellState {
+    /// An empty cell.
+    Empty,
+    /// A crossed out cell.
+    CrossedOut,
+    /// A filled cell.
+    Filled,
+    /// An empty cell the player has marked as possibly filled.
+    Marked,
+    /// A cell the player has tentatively filled, while testing a guess.
+    Tentative,
+}
+
+/// A game of a black and white puzzle.
+#[wasm_bindgen]
+pub struct Game {
+    picross: Picross<SimpleCell>,
+}
+
+#[wasm_bindgen]
+impl Game {
+    /// Starts a game of the puzzle in the `.non` file `text`.
+    #[wasm_bindgen(js_name = fromNon)]
+    pub fn from_non(text: &str) -> Result<Game, JsError> {
+        let puzzle = parse_non(text).map_err(|error| JsError::new(&error.to_string()))?;
+        Ok(Game {
+            picross: Picross::new(puzzle),
+        })
+    }
+
+    /// The puzzle's title, if it has one.
+    #[wasm_bindgen(getter)]
+    pub fn title(&self) -> Option<String> {
+        self.picross.metadata().title.clone()
+    }
+
+    /// The number of columns.
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> usize {
+        self.picross.width()
+    }
+
+    /// The number of rows.
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> usize {
+        self.picross.height()
+    }
+
+    /// Fills the cell at `row` and `column`. Returns whether or not the puzzle is solved
+    /// afterwards, or throws if the cell is locked.
+    pub fn place(&mut self, row: usize, column: usize) -> Result<bool, JsError> {
+        self.make_move(row, column, |picross| picross.place_at(SimpleCell, row, column))
+    }
+
+    /// Crosses out the cell at `row` and `column`. Returns whether or not the puzzle is solved
+    /// afterwards, or throws if the cell is locked.
+    #[wasm_bindgen(js_name = crossOut)]
+    pub fn cross_out(&mut self, row: usize, column: usize) -> Result<bool, JsError> {
+        self.make_move(row, column, |picross| picross.cross_out(row, column))
+    }
+
+    /// Marks the cell at `row` and `column` as possibly filled. Returns whether or not the
+    /// puzzle is solved afterwards, or throws if the cell is locked.
+    pub fn mark(&mut self, row: usize, column: usize) -> Result<bool, JsError> {
+        self.make_move(row, column, |picross| picross.mark_at(row, column))
+    }
+
+    /// Empties the cell at `row` and `column`. Returns whether or not the puzzle is solved
+    /// afterwards, or throws if the cell is locked.
+    pub fn clear(&mut self, row: usize, column: usize) -> Result<bool, JsError> {
+        self.make_move(row, column, |picross| picross.clear_at(row, column))
+    }
+
+    /// Returns the cell at `row` and `column`, if there is one.
+    pub fn cell(&self, row: usize, column: usize) -> Option<CellState> {
+        if !self.contains(row, column) {
+            return None;
+        }
+        Some(match self.picross.get(row, column) {
+            Cell::Empty => CellState::Empty,
+            Cell::CrossedOut => CellState::CrossedOut,
+            Cell::Filled(_) => CellState::Filled,
+            Cell::Marked => CellState::Marked,
+            Cell::Tentative(_) => CellState::Tentative,
+        })
+    }
+
+    /// Returns the block sizes of the clue of row `index`, if there is such a row.
+    #[wasm_bindgen(js_name = rowClue)]
+    pub fn row_clue(&self, index: usize) -> Option<Vec<u32>> {
+        let constraint = self.picross.row_constraints().get(index)?;
+        Some(constraint.iter().map(|entry| entry.size as u32).collect())
+    }
+
+    /// Returns the block sizes of the clue of column `index`, if there is such a column.
+    #[wasm_bindgen(js_name = columnClue)]
+    pub fn column_clue(&self, index: usize) -> Option<Vec<u32>> {
+        let constraint = self.picross.column_constraints().get(index)?;
+        Some(constraint.iter().map(|entry| entry.size as u32).collect())
+    }
+
+    /// Whether or not row `index` satisfies its clue, if there is such a row.
+    #[wasm_bindgen(js_name = isRowSolved)]
+    pub fn is_row_solved(&self, index: usize) -> Option<bool> {
+        self.is_line_solved(0, index)
+    }
+
+    /// Whether or not column `index` satisfies its clue, if there is such a column.
+    #[wasm_bindgen(js_name = isColumnSolved)]
+    pub fn is_column_solved(&self, index: usize) -> Option<bool> {
+        self.is_line_solved(1, index)
+    }
+
+    /// Whether or not the puzzle is solved.
+    #[wasm_bindgen(js_name = isSolved)]
+    pub fn is_solved(&self) -> bool {
+        self.picross.is_solved()
+    }
+
+    /// Undoes the last move. Returns whether or not there was a move to undo.
+    pub fn undo(&mut self) -> bool {
+        self.picross.undo()
+    }
+
+    /// Redoes the last undone move. Returns whether or not there was a move to redo.
+    pub fn redo(&mut self) -> bool {
+        self.picross.redo()
+    }
+}
+
+impl Game {
+    fn contains(&self, row: usize, column: usize) -> bool {
+        self.picross.geometry().contains(row, column)
+    }
+
+    fn is_line_solved(&self, family: usize, index: usize) -> Option<bool> {
+        if index >= self.picross.geometry().line_count(family) {
+            return None;
+        }
+        Some(self.picross.is_line_solved(LineId::new(family, index)))
+    }
+
+    /// Makes a move at `row` and `column`, checking first that there is a cell there, since
+    /// picore panics on positions off the board.
+    fn make_move(
+        &mut self,
+        row: usize,
+        column: usize,
+        action: impl FnOnce(&mut Picross<SimpleCell>) -> Result<bool, LockedError>,
+    ) -> Result<bool, JsError> {
+        if !self.contains(row, column) {
+            return Err(JsError::new(&format!(
+                "there is no cell at row {}, column {}",
+                row.saturating_add(1),
+                column.saturating_add(1)
+            )));
+        }
+        action(&mut self.picross).map_err(|error| JsError::new(&error.to_string()))
+    }
+}
+
+/// A puzzle pack, as read by [`Collection::parse_pack`].
+#[wasm_bindgen]
+pub struct Pack {
+    collection: Collection<SimpleCell>,
+}
+
+#[wasm_bindgen]
+impl Pack {
+    /// Reads the pack file `text`.
+    pub fn parse(text: &str) -> Result<Pack, JsError> {
+        let collection = Collection::parse_pack(text).map_err(|error| JsError::new(&error.to_string()))?;
+        Ok(Pack { collection })
+    }
+
+    /// The pack's title, if it has one.
+    #[wasm_bindgen(getter)]
+    pub fn title(&self) -> Option<String> {
+        self.collection.metadata().title.clone()
+    }
+
+    /// The number of puzzles in the pack.
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.collection.len()
+    }
+
+    /// Starts a game of the puzzle at `index`, if there is one.
+    pub fn game(&self, index: usize) -> Option<Game> {
+        let entry = self.collection.get(index)?;
+        Some(Game {
+            picross: Picross::new(entry.puzzle.clone()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CellState, Game, Pack};
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    // #.
+    // ##
+    const PUZZLE: &str = "title \"Corner\"\nwidth 2\nheight 2\nrows\n1\n2\ncolumns\n2\n1\n";
+
+    // Runs natively too, where the bindings are plain Rust.
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn plays_a_game() {
+        let mut game = Game::from_non(PUZZLE).unwrap();
+        assert_eq!((game.width(), game.height()), (2, 2));
+        assert_eq!(game.title().as_deref(), Some("Corner"));
+        assert_eq!(game.column_clue(0).unwrap(), [2]);
+
+        assert!(!game.place(0, 0).unwrap());
+        assert!(!game.place(1, 0).unwrap());
+        assert_eq!(game.is_column_solved(0), Some(true));
+        assert_eq!(game.is_row_solved(1), Some(false));
+        assert!(game.place(1, 1).unwrap());
+        assert!(game.is_solved());
+
+        assert!(game.undo());
+        assert_eq!(game.cell(1, 1), Some(CellState::Empty));
+        assert!(game.redo());
+        assert_eq!(game.cell(1, 1), Some(CellState::Filled));
+        assert_eq!(game.cell(0, 1), Some(CellState::CrossedOut));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn ignores_positions_off_the_board() {
+        let game = Game::from_non(PUZZLE).unwrap();
+        assert_eq!(game.cell(2, 0), None);
+        assert_eq!(game.row_clue(2), None);
+        assert_eq!(game.column_clue(usize::MAX), None);
+        assert_eq!(game.is_row_solved(2), None);
+        assert_eq!(game.is_column_solved(2), None);
+    }
+
+    // Errors can only be made inside a JavaScript engine.
+    #[cfg(target_arch = "wasm32")]
+    #[wasm_bindgen_test]
+    fn moves_off_the_board_throw() {
+        let mut game = Game::from_non(PUZZLE).unwrap();
+        assert!(game.place(2, 0).is_err());
+        assert!(game.cross_out(0, 2).is_err());
+        assert!(game.mark(usize::MAX, 0).is_err());
+        assert!(game.clear(0, usize::MAX).is_err());
+        assert_eq!(game.cell(0, 0), Some(CellState::Empty));
+    }
+
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    fn loads_packs() {
+        let pack = Pack::parse(&format!("title \"Pack\"\npuzzle\n{}", PUZZLE)).unwrap();
+        assert_eq!(pack.title().as_deref(), Some("Pack"));
+        assert_eq!(pack.length(), 1);
+        assert_eq!(pack.game(0).unwrap().row_clue(1).unwrap(), [2]);
+        assert!(pack.game(1).is_none());
+    }
+}